@@ -0,0 +1,105 @@
+use crate::{Rendering, width_at};
+use {
+    std::ops::Range,
+    unicode_segmentation::UnicodeSegmentation,
+    unicode_bidi::{ParagraphBidiInfo, Level, BidiClass, Direction, bidi_class, get_base_direction},
+};
+
+// -----------------
+
+// brackets swap sides when they're in right-to-left text,
+// so ( opens to the left
+const MIRRORED: [(char, char); 8] = [
+    ('(', ')'),
+    (')', '('),
+    ('[', ']'),
+    (']', '['),
+    ('{', '}'),
+    ('}', '{'),
+    ('<', '>'),
+    ('>', '<'),
+];
+
+// most rows are all left-to-right, and those are printed
+// as they are without running the whole bidi algorithm
+pub fn has_rtl(text: &str) -> bool {
+    text
+    .chars()
+    .any(|character| matches!(
+        bidi_class(character),
+        BidiClass::R | BidiClass::AL | BidiClass::RLE | BidiClass::RLO | BidiClass::RLI
+    ))
+}
+
+// a line goes right-to-left if its first letter with a direction
+// does. lines without any (like numbers) go left-to-right.
+pub fn is_rtl_line(line: &str) -> bool {
+    get_base_direction(line) == Direction::Rtl
+}
+
+// splits a row into runs in the order they're shown on screen,
+// as byte ranges of the row, and whether each one goes right to
+// left. rtl is the direction of the line the row is part of.
+pub fn visual_runs(row: &str, rtl: bool) -> Vec<(Range<usize>, bool)> {
+    if row.is_empty() {
+        return Vec::new()
+    }
+    let level = if rtl {
+        Level::rtl()
+    } else {
+        Level::ltr()
+    };
+    let bidi_info = ParagraphBidiInfo::new(row, Some(level));
+    let (levels, runs) = bidi_info.visual_runs(0..row.len());
+
+    runs
+    .into_iter()
+    .map(|run| {
+        let run_rtl = levels.get(run.start).is_some_and(Level::is_rtl);
+        (run, run_rtl)
+    })
+    .collect()
+}
+
+// right-to-left text is stored in the order it's read, so it's
+// drawn backwards, a grapheme at a time so accents stay put
+pub fn reverse(text: &str) -> String {
+    text
+    .graphemes(true)
+    .rev()
+    .map(|grapheme| {
+        let mut characters = grapheme.chars();
+
+        match (characters.next(), characters.next()) {
+            (Some(character), None) => MIRRORED
+            .iter()
+            .find(|(from, _)| *from == character)
+            .map_or(grapheme.to_string(), |(_, to)| to.to_string()),
+            _ => grapheme.to_string(),
+        }
+    })
+    .collect()
+}
+
+// the column on screen just after the byte at offset, where the
+// cursor goes when it's at offset. in right-to-left text, that's
+// to the left of it.
+pub fn visual_column(row: &str, rtl: bool, offset: usize, start_column: usize, rendering: Rendering) -> usize {
+    let mut column = start_column;
+
+    for (run, run_rtl) in visual_runs(row, rtl) {
+        let run_text = row.get(run.clone()).unwrap_or_default();
+
+        if run.start < offset && offset <= run.end {
+            let before_cursor = if run_rtl {
+                row.get(offset..run.end)
+            } else {
+                row.get(run.start..offset)
+            };
+            return column.saturating_add(width_at(before_cursor.unwrap_or_default(), column, rendering))
+        }
+        column = column.saturating_add(width_at(run_text, column, rendering));
+    }
+
+    column
+}
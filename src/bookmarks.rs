@@ -0,0 +1,80 @@
+use crate::document_stats_path;
+use {
+    std::{
+        path::Path,
+        fs::{File, rename},
+        io::{BufReader, BufRead, BufWriter, Write, Error},
+    },
+    log::error,
+};
+
+// -----------------
+
+const BOOKMARKS_EXT: &str = "bookmarks";
+
+// a named place in a file, kept as the byte offset of the start
+// of its line, so it stays put however the text gets wrapped
+#[derive(Clone)]
+pub struct Bookmark {
+    pub offset: usize,
+    pub name: String,
+}
+
+impl Bookmark {
+    // the name goes last, since it can have spaces in it
+    fn from_line(line: &str) -> Option<Self> {
+        let (offset, name) = line.split_once(' ')?;
+
+        Some(Self {
+            offset: offset.strip_prefix("offset=")?.parse().ok()?,
+            name: name.strip_prefix("name=")?.to_string(),
+        })
+    }
+
+    fn to_line(&self) -> String {
+        format!("offset={} name={}", self.offset, self.name)
+    }
+}
+
+pub fn read_bookmarks(document_path: &Path) -> Vec<Bookmark> {
+    let mut bookmarks = Vec::new();
+    let Some(path) = document_stats_path(document_path, BOOKMARKS_EXT) else {
+        return bookmarks
+    };
+
+    if let Ok(bookmarks_file) = File::open(&path) {
+        for (line_index, file_line) in BufReader::new(bookmarks_file).lines().enumerate() {
+            if let Some(bookmark) = file_line.ok().as_deref().and_then(Bookmark::from_line) {
+                bookmarks.push(bookmark);
+            } else {
+                error!("[bookmarks.rs]: could not read line {} of {}. skipping it.", line_index.saturating_add(1), path.display());
+            }
+        }
+    }
+
+    bookmarks
+}
+
+// bookmarks can be renamed and removed, so unlike the stats
+// files, the whole list is written out again each time
+pub fn write_bookmarks(bookmarks: &[Bookmark], document_path: &Path) {
+    let result = document_stats_path(document_path, BOOKMARKS_EXT)
+    .map_or(Ok(()), |path| write_file(bookmarks, &path));
+
+    if let Err(error_msg) = result {
+        error!("[bookmarks.rs]: {error_msg} - could not write bookmarks.");
+    }
+}
+
+fn write_file(bookmarks: &[Bookmark], path: &Path) -> Result<(), Error> {
+    let mut tmp_path = path.to_path_buf();
+    tmp_path.set_extension("tmp");
+    let mut bookmarks_file = BufWriter::new(File::create(&tmp_path)?);
+
+    for bookmark in bookmarks {
+        writeln!(bookmarks_file, "{}", bookmark.to_line())?;
+    }
+    bookmarks_file.flush()?;
+    rename(&tmp_path, path)?;
+    Ok(())
+}
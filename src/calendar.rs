@@ -0,0 +1,159 @@
+use crate::{Terminal, Position, Progress, longest_streak, current_streak};
+use {
+    std::{
+        collections::BTreeMap,
+        io::Error,
+    },
+    crossterm::event::{Event, KeyEventKind, read},
+    chrono::{Datelike, Days, Local, NaiveDate},
+};
+
+// -----------------
+
+// how much room the weekday labels take up on the left
+const LABEL_WIDTH: usize = 4;
+const MAX_WEEKS: usize = 53;
+const WEEKDAYS: [&str; 7] = ["mon", "", "wed", "", "fri", "", "sun"];
+const EMPTY_DAY: char = '·';
+const LEVELS: [char; 4] = ['░', '▒', '▓', '█'];
+
+// a heatmap of words written per day, one column per week,
+// built from the same progress file as the daily goal
+pub struct Calendar {
+    days: BTreeMap<NaiveDate, usize>,
+}
+
+impl Calendar {
+    pub fn load() -> Self {
+        Self::from(&Progress::load())
+    }
+
+    // the editor already has the progress loaded
+    pub fn from(progress: &Progress) -> Self {
+        Self {
+            days: progress.days().iter().copied().collect(),
+        }
+    }
+
+    fn writing_days(&self) -> Vec<NaiveDate> {
+        self.days
+        .iter()
+        .filter(|(_, words)| **words > 0)
+        .map(|(date, _)| *date)
+        .collect()
+    }
+
+    // builds the calendar as lines of text that fit in
+    // the given width, with the current week on the right
+    pub fn lines(&self, width: usize) -> Vec<String> {
+        let today = Local::now().date_naive();
+        let weeks = (width.saturating_sub(LABEL_WIDTH) / 2).clamp(1, MAX_WEEKS);
+        let days_back = u64::from(today.weekday().num_days_from_monday())
+        .saturating_add((weeks as u64).saturating_sub(1).saturating_mul(7));
+        let first_day = today.checked_sub_days(Days::new(days_back)).unwrap_or(today);
+        let busiest_day = self.days
+        .range(first_day..)
+        .map(|(_, words)| *words)
+        .max()
+        .unwrap_or(0);
+        let writing_days = self.writing_days();
+        let total_words: usize = self.days.values().sum();
+        let mut lines = vec![
+            String::from("writing calendar"),
+            String::new(),
+            month_labels(first_day, weeks),
+        ];
+
+        for (weekday, label) in WEEKDAYS.iter().enumerate() {
+            let mut line = format!("{label:<LABEL_WIDTH$}");
+
+            for week in 0..weeks {
+                let offset = (week as u64).saturating_mul(7).saturating_add(weekday as u64);
+
+                match first_day.checked_add_days(Days::new(offset)) {
+                    Some(date) if date <= today => {
+                        let words = self.days.get(&date).copied().unwrap_or(0);
+                        line.push(level(words, busiest_day));
+                        line.push(' ');
+                    },
+                    _ => line.push_str("  "),
+                }
+            }
+            lines.push(line);
+        }
+
+        lines.push(String::new());
+        lines.push(format!("less {EMPTY_DAY} {} more", LEVELS.map(String::from).join(" ")));
+        lines.push(String::new());
+        lines.push(format!(
+            "current streak: {} days | longest streak: {} days",
+            current_streak(&writing_days, today),
+            longest_streak(&writing_days),
+        ));
+        lines.push(format!("{total_words} words written over {} days", writing_days.len()));
+        lines.push(String::new());
+        lines.push(String::from("press any key to go back."));
+
+        lines
+    }
+
+    // shows the calendar on its own, for the welcome screen
+    pub fn show(&self) -> Result<(), Error> {
+        let mut terminal = Terminal::default();
+
+        Terminal::init()?;
+        terminal.cursor_hide()?;
+        terminal.move_cursor(&Position::default())?;
+        for line in self.lines(terminal.width) {
+            terminal.queue_print(&line)?;
+            terminal.clear_line()?;
+            terminal.new_line()?;
+        }
+        terminal.flush()?;
+
+        loop {
+            if let Event::Key(key) = read()? {
+                if key.kind == KeyEventKind::Press {
+                    break
+                }
+            }
+        }
+        Terminal::quit(String::new())
+    }
+}
+
+// splits the words written on a day into one of four levels,
+// relative to the busiest day on the calendar
+fn level(words: usize, busiest_day: usize) -> char {
+    if words == 0 {
+        return EMPTY_DAY
+    }
+    let level = words
+    .saturating_mul(LEVELS.len())
+    .div_ceil(busiest_day.max(1))
+    .clamp(1, LEVELS.len());
+
+    LEVELS.get(level.saturating_sub(1)).copied().unwrap_or(EMPTY_DAY)
+}
+
+// puts the name of each month above the week it starts in
+fn month_labels(first_day: NaiveDate, weeks: usize) -> String {
+    let mut labels = " ".repeat(LABEL_WIDTH);
+    let mut last_month = None;
+
+    for week in 0..weeks {
+        let Some(week_start) = first_day.checked_add_days(Days::new((week as u64).saturating_mul(7))) else {
+            break
+        };
+        let column = LABEL_WIDTH.saturating_add(week.saturating_mul(2));
+
+        if last_month != Some(week_start.month()) && labels.len() <= column {
+            labels.push_str(&" ".repeat(column.saturating_sub(labels.len())));
+            labels.push_str(&week_start.format("%b").to_string().to_lowercase());
+        }
+        last_month = Some(week_start.month());
+    }
+    labels.truncate(LABEL_WIDTH.saturating_add(weeks.saturating_mul(2)));
+
+    labels
+}
@@ -1,10 +1,11 @@
-use crate::get_conf_or_log_path;
+use crate::{get_conf_or_log_path, Theme, THEME_KEYS, Focus, Gutter, no_color};
 use {
     std::{
         io::{BufReader, BufRead},
         fs::File,
     },
     crossterm::cursor::SetCursorStyle,
+    hypher::Lang,
     log::{error, warn},
 };
 
@@ -19,10 +20,34 @@ pub struct Config {
     pub open_search: bool,
     pub count_on_quit: bool,
     pub line_numbers: bool,
+    pub gutter: Gutter,
     pub quit_times: u8,
     pub save_time: u8,
     pub save_words: u8,
     pub cursor_style: SetCursorStyle,
+    pub daily_goal: usize,
+    pub document_goal: usize,
+    pub goal_bell: bool,
+    pub sprint_length: usize,
+    pub sprint_lock: bool,
+    pub break_marker: bool,
+    pub text_width: usize,
+    pub tab_width: usize,
+    pub hanging_indent: bool,
+    pub line_spacing: usize,
+    pub paragraph_spacing: usize,
+    pub center_text: bool,
+    pub zen: bool,
+    pub hyphenation: Option<Lang>,
+    pub render_markdown: bool,
+    pub dim_markers: bool,
+    pub theme: Theme,
+    pub focus: Focus,
+    pub search_ignore_case: bool,
+    pub search_whole_word: bool,
+    pub search_regex: bool,
+    pub vi_keys: bool,
+    pub mouse: bool,
 }
 
 impl Default for Config {
@@ -32,10 +57,34 @@ impl Default for Config {
             open_search: true,
             count_on_quit: true,
             line_numbers: true,
+            gutter: Gutter::Absolute,
             quit_times: 2,
             save_time: 5,
             save_words: 6,
             cursor_style: SetCursorStyle::DefaultUserShape,
+            daily_goal: 0,
+            document_goal: 0,
+            goal_bell: false,
+            sprint_length: 25,
+            sprint_lock: false,
+            break_marker: true,
+            text_width: 0,
+            tab_width: 2,
+            hanging_indent: false,
+            line_spacing: 1,
+            paragraph_spacing: 0,
+            center_text: true,
+            zen: false,
+            hyphenation: None,
+            render_markdown: true,
+            dim_markers: true,
+            theme: Theme::default(),
+            focus: Focus::Paragraph,
+            search_ignore_case: true,
+            search_whole_word: false,
+            search_regex: false,
+            vi_keys: false,
+            mouse: false,
         }
     }
 }
@@ -58,10 +107,37 @@ impl Config {
                     let mut open_search = true;
                     let mut count_on_quit = true;
                     let mut line_numbers = true;
+                    let mut gutter = Gutter::Absolute;
                     let mut quit_times = 2;
                     let mut save_time = 5;
                     let mut save_words = 6;
                     let mut cursor_style = SetCursorStyle::DefaultUserShape;
+                    let mut daily_goal = 0;
+                    let mut document_goal = 0;
+                    let mut goal_bell = false;
+                    let mut sprint_length = 25;
+                    let mut sprint_lock = false;
+                    let mut break_marker = true;
+                    let mut text_width = 0;
+                    let mut tab_width = 2;
+                    let mut hanging_indent = false;
+                    let mut line_spacing = 1;
+                    let mut paragraph_spacing = 0;
+                    let mut center_text = true;
+                    let mut zen = false;
+                    let mut hyphenation = None;
+                    let mut render_markdown = true;
+                    let mut dim_markers = true;
+                    let mut theme = Theme::default();
+                    let mut focus = Focus::Paragraph;
+                    let mut search_ignore_case = true;
+                    let mut search_whole_word = false;
+                    let mut search_regex = false;
+                    let mut vi_keys = false;
+                    let mut mouse = false;
+                    // these are applied once the whole file has been read,
+                    // so they always go on top of the theme
+                    let mut theme_options = Vec::new();
 
                     for (line_index, file_line) in reader.lines().enumerate() {
                         if let Ok(config_line) = file_line {
@@ -106,6 +182,14 @@ impl Config {
                                     };
                                     continue
                                 };
+                                if config_line.contains("gutter = ") {
+                                    if let Some(mode) = parse_value(&config_line).and_then(Gutter::from_name) {
+                                        gutter = mode;
+                                    } else {
+                                        error!("[config.rs]: invalid gutter value at line {}. using default.", line_index.saturating_add(1));
+                                    };
+                                    continue
+                                };
                                 if config_line.contains("cursor-style = ") {
                                     if let Some(style) = match_cursor_style(&config_line) {
                                         cursor_style = style;
@@ -147,6 +231,226 @@ impl Config {
                                     };
                                     continue
                                 };
+                                if config_line.contains("daily-goal = ") {
+                                    if let Some(value) = parse_number(&config_line) {
+                                        daily_goal = value;
+                                    } else {
+                                        error!("[config.rs]: invalid daily-goal value at line {}. using default.", line_index.saturating_add(1));
+                                    };
+                                    continue
+                                };
+                                if config_line.contains("document-goal = ") {
+                                    if let Some(value) = parse_number(&config_line) {
+                                        document_goal = value;
+                                    } else {
+                                        error!("[config.rs]: invalid document-goal value at line {}. using default.", line_index.saturating_add(1));
+                                    };
+                                    continue
+                                };
+                                if config_line.contains("goal-bell = ") {
+                                    if config_line.contains("false") {
+                                        goal_bell = false;
+                                    } else if config_line.contains("true") {
+                                        goal_bell = true;
+                                    } else {
+                                        error!("[config.rs]: invalid goal-bell value at line {}. using default.", line_index.saturating_add(1));
+                                    };
+                                    continue
+                                };
+                                if config_line.contains("sprint-length = ") {
+                                    if let Some(value) = parse_number(&config_line).filter(|value| *value > 0) {
+                                        sprint_length = value;
+                                    } else {
+                                        error!("[config.rs]: invalid sprint-length value at line {}. using default.", line_index.saturating_add(1));
+                                    };
+                                    continue
+                                };
+                                if config_line.contains("sprint-lock = ") {
+                                    if config_line.contains("false") {
+                                        sprint_lock = false;
+                                    } else if config_line.contains("true") {
+                                        sprint_lock = true;
+                                    } else {
+                                        error!("[config.rs]: invalid sprint-lock value at line {}. using default.", line_index.saturating_add(1));
+                                    };
+                                    continue
+                                };
+                                if config_line.contains("break-marker = ") {
+                                    if config_line.contains("false") {
+                                        break_marker = false;
+                                    } else if config_line.contains("true") {
+                                        break_marker = true;
+                                    } else {
+                                        error!("[config.rs]: invalid break-marker value at line {}. using default.", line_index.saturating_add(1));
+                                    };
+                                    continue
+                                };
+                                if config_line.contains("text-width = ") {
+                                    if let Some(value) = parse_number(&config_line) {
+                                        text_width = value;
+                                    } else {
+                                        error!("[config.rs]: invalid text-width value at line {}. using default.", line_index.saturating_add(1));
+                                    };
+                                    continue
+                                };
+                                if config_line.contains("tab-width = ") {
+                                    if let Some(value) = parse_number(&config_line).filter(|value| *value > 0) {
+                                        tab_width = value;
+                                    } else {
+                                        error!("[config.rs]: invalid tab-width value at line {}. using default.", line_index.saturating_add(1));
+                                    };
+                                    continue
+                                };
+                                if config_line.contains("hanging-indent = ") {
+                                    if config_line.contains("false") {
+                                        hanging_indent = false;
+                                    } else if config_line.contains("true") {
+                                        hanging_indent = true;
+                                    } else {
+                                        error!("[config.rs]: invalid hanging-indent value at line {}. using default.", line_index.saturating_add(1));
+                                    };
+                                    continue
+                                };
+                                if config_line.contains("line-spacing = ") {
+                                    if let Some(value) = parse_number(&config_line).filter(|value| *value > 0) {
+                                        line_spacing = value;
+                                    } else {
+                                        error!("[config.rs]: invalid line-spacing value at line {}. using default.", line_index.saturating_add(1));
+                                    };
+                                    continue
+                                };
+                                if config_line.contains("paragraph-spacing = ") {
+                                    if let Some(value) = parse_number(&config_line) {
+                                        paragraph_spacing = value;
+                                    } else {
+                                        error!("[config.rs]: invalid paragraph-spacing value at line {}. using default.", line_index.saturating_add(1));
+                                    };
+                                    continue
+                                };
+                                if config_line.contains("center-text = ") {
+                                    if config_line.contains("false") {
+                                        center_text = false;
+                                    } else if config_line.contains("true") {
+                                        center_text = true;
+                                    } else {
+                                        error!("[config.rs]: invalid center-text value at line {}. using default.", line_index.saturating_add(1));
+                                    };
+                                    continue
+                                };
+                                if config_line.contains("zen = ") {
+                                    if config_line.contains("false") {
+                                        zen = false;
+                                    } else if config_line.contains("true") {
+                                        zen = true;
+                                    } else {
+                                        error!("[config.rs]: invalid zen value at line {}. using default.", line_index.saturating_add(1));
+                                    };
+                                    continue
+                                };
+                                if config_line.contains("hyphenation = ") {
+                                    if config_line.contains("off") {
+                                        hyphenation = None;
+                                    } else if let Some(lang) = match_hyphenation_lang(&config_line) {
+                                        hyphenation = Some(lang);
+                                    } else {
+                                        error!("[config.rs]: invalid hyphenation value at line {}. using default.", line_index.saturating_add(1));
+                                    };
+                                    continue
+                                };
+                                if config_line.contains("render-markdown = ") {
+                                    if config_line.contains("false") {
+                                        render_markdown = false;
+                                    } else if config_line.contains("true") {
+                                        render_markdown = true;
+                                    } else {
+                                        error!("[config.rs]: invalid render-markdown value at line {}. using default.", line_index.saturating_add(1));
+                                    };
+                                    continue
+                                };
+                                if config_line.contains("dim-markers = ") {
+                                    if config_line.contains("false") {
+                                        dim_markers = false;
+                                    } else if config_line.contains("true") {
+                                        dim_markers = true;
+                                    } else {
+                                        error!("[config.rs]: invalid dim-markers value at line {}. using default.", line_index.saturating_add(1));
+                                    };
+                                    continue
+                                };
+                                if config_line.contains("focus = ") {
+                                    if config_line.contains("paragraph") {
+                                        focus = Focus::Paragraph;
+                                    } else if config_line.contains("sentence") {
+                                        focus = Focus::Sentence;
+                                    } else {
+                                        error!("[config.rs]: invalid focus value at line {}. using default.", line_index.saturating_add(1));
+                                    };
+                                    continue
+                                };
+                                if config_line.contains("search-ignore-case = ") {
+                                    if config_line.contains("false") {
+                                        search_ignore_case = false;
+                                    } else if config_line.contains("true") {
+                                        search_ignore_case = true;
+                                    } else {
+                                        error!("[config.rs]: invalid search-ignore-case value at line {}. using default.", line_index.saturating_add(1));
+                                    };
+                                    continue
+                                };
+                                if config_line.contains("search-whole-word = ") {
+                                    if config_line.contains("false") {
+                                        search_whole_word = false;
+                                    } else if config_line.contains("true") {
+                                        search_whole_word = true;
+                                    } else {
+                                        error!("[config.rs]: invalid search-whole-word value at line {}. using default.", line_index.saturating_add(1));
+                                    };
+                                    continue
+                                };
+                                if config_line.contains("search-regex = ") {
+                                    if config_line.contains("false") {
+                                        search_regex = false;
+                                    } else if config_line.contains("true") {
+                                        search_regex = true;
+                                    } else {
+                                        error!("[config.rs]: invalid search-regex value at line {}. using default.", line_index.saturating_add(1));
+                                    };
+                                    continue
+                                };
+                                if config_line.contains("vi-keys = ") {
+                                    if config_line.contains("false") {
+                                        vi_keys = false;
+                                    } else if config_line.contains("true") {
+                                        vi_keys = true;
+                                    } else {
+                                        error!("[config.rs]: invalid vi-keys value at line {}. using default.", line_index.saturating_add(1));
+                                    };
+                                    continue
+                                };
+                                if config_line.contains("mouse = ") {
+                                    if config_line.contains("false") {
+                                        mouse = false;
+                                    } else if config_line.contains("true") {
+                                        mouse = true;
+                                    } else {
+                                        error!("[config.rs]: invalid mouse value at line {}. using default.", line_index.saturating_add(1));
+                                    };
+                                    continue
+                                };
+                                if config_line.contains("theme = ") {
+                                    if let Some(preset) = parse_value(&config_line).and_then(Theme::preset) {
+                                        theme = preset;
+                                    } else {
+                                        error!("[config.rs]: invalid theme value at line {}. using default.", line_index.saturating_add(1));
+                                    };
+                                    continue
+                                };
+                                if let Some(key) = THEME_KEYS
+                                .iter()
+                                .find(|key| config_line.contains(&format!("{key} = "))) {
+                                    theme_options.push((line_index, *key, parse_value(&config_line).unwrap_or_default().to_string()));
+                                    continue
+                                };
                                 if !config_line.is_empty() {
                                     warn!("[config.rs]: unknown input on config file line {}", line_index.saturating_add(1));    
                                 }
@@ -155,15 +459,47 @@ impl Config {
                             error!("[config.rs]: could not read config file line {}", line_index.saturating_add(1));
                         };
                     };
+                    for (line_index, key, value) in theme_options {
+                        if !theme.set(key, &value) {
+                            error!("[config.rs]: invalid {key} value at line {}. using the theme's.", line_index.saturating_add(1));
+                        }
+                    }
+                    if no_color() {
+                        theme = theme.without_color();
+                    }
                     return Self {
                         start_edit,
                         open_search,
                         count_on_quit,
                         line_numbers,
+                        gutter,
                         quit_times,
                         save_time,
                         save_words,
                         cursor_style,
+                        daily_goal,
+                        document_goal,
+                        goal_bell,
+                        sprint_length,
+                        sprint_lock,
+                        break_marker,
+                        text_width,
+                        tab_width,
+                        hanging_indent,
+                        line_spacing,
+                        paragraph_spacing,
+                        center_text,
+                        zen,
+                        hyphenation,
+                        render_markdown,
+                        dim_markers,
+                        theme,
+                        focus,
+                        search_ignore_case,
+                        search_whole_word,
+                        search_regex,
+                        vi_keys,
+                        mouse,
                     };
                 },
                 Err(error_msg) => {
@@ -187,3 +523,25 @@ fn match_cursor_style(style: &str) -> Option<SetCursorStyle> {
         _ => None,
     }
 }
+
+// only the languages brr is built with patterns for
+// will match, see hypher's features in Cargo.toml
+fn match_hyphenation_lang(config_line: &str) -> Option<Lang> {
+    parse_value(config_line)
+    .and_then(|value| value.as_bytes().try_into().ok())
+    .and_then(Lang::from_iso)
+}
+
+// everything after the equals sign, without the spaces around it
+fn parse_value(config_line: &str) -> Option<&str> {
+    config_line
+    .split_once('=')
+    .map(|(_, value)| value.trim())
+}
+
+// unlike the single digit options above, goals can be
+// any size, so parse everything after the equals sign.
+pub fn parse_number(config_line: &str) -> Option<usize> {
+    parse_value(config_line)
+    .and_then(|value| value.parse::<usize>().ok())
+}
@@ -2,6 +2,8 @@ use {
     std::io::{Error, stdout},
     crossterm::{
         cursor::SetCursorStyle,
+        event::DisableMouseCapture,
+        style::{ResetColor, SetAttribute, Attribute::Reset},
         terminal::{disable_raw_mode, LeaveAlternateScreen},
         ExecutableCommand,
     },
@@ -15,6 +17,15 @@ pub fn die(error_msg: Error) {
     if let Err(error_msg) = stdout().execute(SetCursorStyle::DefaultUserShape) {
         error!("[die.rs]: {error_msg} - could not reset cursor style.");
     };
+    if let Err(error_msg) = stdout().execute(DisableMouseCapture) {
+        error!("[die.rs]: {error_msg} - could not disable mouse capture.");
+    };
+    if let Err(error_msg) = stdout().execute(SetAttribute(Reset)) {
+        error!("[die.rs]: {error_msg} - could not reset attributes.");
+    };
+    if let Err(error_msg) = stdout().execute(ResetColor) {
+        error!("[die.rs]: {error_msg} - could not reset colours.");
+    };
     if let Err(error_msg) = disable_raw_mode() {
         error!("[die.rs]: {error_msg} - could not disable raw mode.");
     };
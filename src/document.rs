@@ -1,8 +1,12 @@
-use crate::{Terminal, Metadata, DisplayRow, AppendBuffer, Position, die, SaveType};
+use crate::{Terminal, Metadata, DisplayRow, AppendBuffer, Position, die, SaveType, Span, line_spans, style_rows, Gutter, LineStats, SaveRecord, TIME_WIDTH, line_stats, has_rtl, is_rtl_line, visual_column};
 use {
     unicode_segmentation::UnicodeSegmentation,
+    unicode_width::UnicodeWidthStr,
+    unicode_linebreak::linebreaks,
     words_count::WordsCount,
     log::{error, warn, info, trace},
+    hypher::{hyphenate, Lang},
+    chrono::{DateTime, Local},
     std::{
         cmp::Ordering,
         time::Instant,
@@ -14,17 +18,50 @@ use {
 
 // -----------------
 
+// how the invisible characters are shown when they're turned on.
+// each glyph takes up a single column.
+pub const SPACE_GLYPH: &str = "·";
+const TAB_GLYPH: &str = "→";
+const NO_BREAK_SPACE_GLYPH: &str = "␣";
+const ZERO_WIDTH_GLYPH: &str = "¦";
+pub const LINE_END_GLYPH: &str = "¬";
+// no-break space, narrow no-break space and figure space
+const NO_BREAK_SPACES: [&str; 3] = ["\u{a0}", "\u{202f}", "\u{2007}"];
+
+// see config.rs. the bools are layout options copied over from the config.
+#[allow(clippy::struct_excessive_bools)]
 pub struct Document {
     pub metadata: Metadata,
     pub content: String,
     pub amt_lines: usize,
-    pub line_no_digits: usize,
+    pub gutter_digits: usize,
     pub append_buffer: AppendBuffer,
     pub file_drows: Vec<DisplayRow>,
     pub buf_drows: Vec<DisplayRow>,
     pub last_edit: Instant,
     pub count: WordsCount,
     pub start_count: WordsCount,
+    pub saves: usize,
+    // where and when this session started
+    pub start_offset: usize,
+    pub opened_at: DateTime<Local>,
+    // how rows are laid out, set from the config by the editor.
+    // a text width of 0 means wrap at the edge of the window.
+    pub text_width: usize,
+    pub show_gutter: bool,
+    pub hyphenation: Option<Lang>,
+    pub markdown: bool,
+    pub tab_width: usize,
+    pub hanging_indent: bool,
+    pub invisibles: bool,
+    // the last column is kept clear for the scrollbar
+    pub scrollbar: bool,
+    pub gutter: Gutter,
+    // only counted up if the gutter needs them, see Gutter::needs_stats()
+    pub file_line_stats: Vec<LineStats>,
+    pub buf_line_stats: Vec<LineStats>,
+    // every save that added to the file, oldest first
+    pub save_history: Vec<SaveRecord>,
 }
 
 impl Document {
@@ -66,26 +103,37 @@ impl Document {
         }
 
         let start_count = count.clone();
+        let start_offset = content.len();
         let amt_lines = content.lines().count();
 
-        // using successors() to compute the number of digits
-        // in the number of lines in the file.
-        let line_no_digits = successors(
-            Some(amt_lines), 
-            |num| Some(num / 10).filter(|u| *u > 0)
-        ).count();
+        let gutter_digits = count_digits(amt_lines);
 
         Self { 
             metadata,
-            content: content.to_string(),
+            content,
             amt_lines,
-            line_no_digits,
+            gutter_digits,
             append_buffer: AppendBuffer::default(),
             file_drows: Vec::new(),
             buf_drows: Vec::new(),
             last_edit: Instant::now(),
             count,
             start_count,
+            saves: 0,
+            start_offset,
+            opened_at: Local::now(),
+            text_width: 0,
+            show_gutter: true,
+            hyphenation: None,
+            markdown: false,
+            tab_width: 4,
+            hanging_indent: false,
+            invisibles: false,
+            scrollbar: false,
+            gutter: Gutter::Absolute,
+            file_line_stats: Vec::new(),
+            buf_line_stats: Vec::new(),
+            save_history: Vec::new(),
         }
     }
 
@@ -97,6 +145,7 @@ impl Document {
             &tmp_path.display()
         );
         let mut save_file = BufWriter::new(File::create(&tmp_path)?);
+        let start = self.content.len();
 
         // words here is set in the config file and
         // refers to how many words brr should save
@@ -117,15 +166,24 @@ impl Document {
             self.append_buffer.buffer.clear();
         }
 
+        if self.content.len() > start {
+            self.save_history.push(SaveRecord {
+                start,
+                end: self.content.len(),
+                saved_at: Local::now().fixed_offset(),
+            });
+        }
         self.count = words_count::count(&self.content);
         
         save_file.write_all(self.content.as_bytes())?;
 
         self.wrap_file();
         self.wrap_buffer();
+        self.update_gutter();
         rename(&tmp_path, &self.metadata.path)?;
 
         self.last_edit = Instant::now();
+        self.saves = self.saves.saturating_add(1);
 
         Ok(())
     }
@@ -135,18 +193,18 @@ impl Document {
     // every keypress, but it still happens often enough that 
     // i would like to find a better solution
     pub fn wrap_file(&mut self) {
-        // get width to wrap at from terminal width - gutter_size
-        // also add 1 to the gutter size to make room for a space
-        // between the gutter and the row.
-        let max_width = Terminal::get_term_size().0.saturating_sub(self.line_no_digits.saturating_add(1));
-        
         self.file_drows.clear();
         self.file_drows = to_display_rows(
             0,
             1,
             &self.content,
-            max_width,
+            "",
+            self.wrap_options(),
         );
+        self.file_line_stats.clear();
+        if self.gutter.needs_stats() {
+            self.file_line_stats = line_stats(&self.content, 0, None);
+        }
 
         let last_drow_index = self.file_drows.len().saturating_sub(1);
 
@@ -190,10 +248,6 @@ impl Document {
     }
 
     pub fn wrap_buffer(&mut self) {
-        // get width to wrap at from terminal width - gutter_size
-        // also add 1 to the gutter size to make room for a space
-        // between the gutter and the row.
-        let max_width = Terminal::get_term_size().0.saturating_sub(self.line_no_digits.saturating_add(1));
         let start_line_no = if let Some(last_drow) = self.file_drows.last() {
             last_drow.line_no
         } else {
@@ -201,12 +255,15 @@ impl Document {
         };
         
         self.buf_drows.clear();
+        // the buffer's first line carries on from the file's last line
         self.buf_drows = to_display_rows(
             self.append_buffer.join_pos.x, 
             start_line_no,
             &self.append_buffer.buffer,
-            max_width,
+            self.content.rsplit('\n').next().unwrap_or_default(),
+            self.wrap_options(),
         );
+        self.count_buffer_lines();
 
         // if there is a newline at the end of the buffer,
         // add an extra display row so that it doesn't get
@@ -229,9 +286,108 @@ impl Document {
         };
     }
 
+    // the buffer's first line carries on from the file's last
+    // line, so that line gets counted again with the buffer on it
+    fn count_buffer_lines(&mut self) {
+        self.buf_line_stats.clear();
+        if !self.gutter.needs_stats() || self.append_buffer.buffer.is_empty() {
+            return
+        }
+        let last_line = self.content.rsplit('\n').next().unwrap_or_default();
+        let last_offset = self.content.len().saturating_sub(last_line.len());
+        let previous = self.file_line_stats
+        .len()
+        .checked_sub(2)
+        .and_then(|index| self.file_line_stats.get(index));
+
+        self.buf_line_stats = line_stats(
+            &format!("{last_line}{}", self.append_buffer.buffer),
+            last_offset,
+            previous,
+        );
+    }
+
+    // line numbers start at 1, and the buffer's stats take over
+    // from the file's at the file's last line
+    pub fn stats_for_line(&self, line_no: usize) -> Option<&LineStats> {
+        let line_index = line_no.checked_sub(1)?;
+        let buf_start = self.file_line_stats.len().saturating_sub(1);
+
+        if !self.buf_line_stats.is_empty() && line_index >= buf_start {
+            self.buf_line_stats.get(line_index.saturating_sub(buf_start))
+        } else {
+            self.file_line_stats.get(line_index)
+        }
+    }
+
+    // the gutter is as wide as the biggest thing it shows, plus
+    // 1 to make room for a space between the gutter and the row.
+    pub fn gutter_width(&self) -> usize {
+        if self.show_gutter {
+            self.gutter_digits.saturating_add(1)
+        } else {
+            0
+        }
+    }
+
+    // get width to wrap at from terminal width - gutter_size, or
+    // from the text width if that's narrower. rows are always
+    // kept shorter than max_width, so add 1 to the text width
+    // to get exactly that many columns of text.
+    pub fn wrap_width(&self) -> usize {
+        let available_width = Terminal::get_term_size().0
+        .saturating_sub(self.gutter_width())
+        .saturating_sub(usize::from(self.scrollbar));
+
+        if self.text_width > 0 {
+            available_width.min(self.text_width.saturating_add(1))
+        } else {
+            available_width
+        }
+    }
+
+    fn wrap_options(&self) -> Wrap {
+        Wrap {
+            max_width: self.wrap_width(),
+            hyphenation: self.hyphenation,
+            markdown: self.markdown,
+            rendering: self.rendering(),
+            hanging_indent: self.hanging_indent,
+        }
+    }
+
+    pub fn rendering(&self) -> Rendering {
+        Rendering {
+            tab_width: self.tab_width,
+            invisibles: self.invisibles,
+        }
+    }
+
+    // the column the cursor goes at in edit mode. that's the end of
+    // the last row, unless it has right-to-left text on it.
+    pub fn append_column(&self) -> usize {
+        let (file_drow, buf_drow) = self.get_display_row(self.display_len().saturating_sub(1));
+        let Some(last_drow) = buf_drow.or(file_drow) else {
+            return 0
+        };
+        let row = format!(
+            "{}{}",
+            file_drow.map_or("", |drow| drow.content.as_str()),
+            buf_drow.map_or("", |drow| drow.content.as_str()),
+        );
+
+        if !has_rtl(&row) {
+            return last_drow.len
+        }
+        let indent = file_drow.or(buf_drow).map_or(0, |drow| drow.indent);
+        visual_column(&row, last_drow.rtl, row.len(), indent, self.rendering())
+    }
+
     pub fn get_display_row(&self, index: usize) -> (Option<&DisplayRow>, Option<&DisplayRow>) {
         let join_index = self.append_buffer.join_pos.y;
-        let file_drow_count = self.file_drows.len();
+        // an empty file has no joining row, but counting one
+        // anyway lines the buffer's rows up with the index
+        let file_drow_count = self.file_drows.len().max(1);
         // this translates the passed index to one within
         // the buffer by subtracting the total amount of
         // file display rows from it and adding one to
@@ -252,17 +408,142 @@ impl Document {
         }
     }
 
+    pub fn line_no_at(&self, index: usize) -> usize {
+        match self.get_display_row(index) {
+            (Some(file_drow), _) => file_drow.line_no,
+            (None, Some(buf_drow)) => buf_drow.line_no,
+            (None, None) => 0,
+        }
+    }
+
+    pub fn first_row_of_line(&self, line_no: usize) -> Option<usize> {
+        (0..self.display_len()).find(|index| self.line_no_at(*index) == line_no)
+    }
+
+    // how many bytes of text are on the display row at index,
+    // from both the file and the buffer
+    pub fn content_len_at(&self, index: usize) -> usize {
+        let (file_drow, buf_drow) = self.get_display_row(index);
+
+        [file_drow, buf_drow]
+        .iter()
+        .flatten()
+        .map(|drow| drow.content.len())
+        .sum()
+    }
+
+    // how many bytes of its line come before the display row at index
+    pub fn offset_in_line(&self, index: usize) -> usize {
+        let line_no = self.line_no_at(index);
+
+        (0..index)
+        .rev()
+        .take_while(|previous| self.line_no_at(*previous) == line_no)
+        .map(|previous| self.content_len_at(previous))
+        .sum()
+    }
+
+    // which line the byte at offset in the file is on, and where
+    // in the file that line starts. offsets past the end of the
+    // file are in the buffer, as if it had been saved.
+    pub fn line_at_offset(&self, offset: usize) -> Option<(usize, usize)> {
+        let content_len = self.content.len();
+        let content_before = self.content.get(..offset.min(content_len))?;
+        let buffer_before = self.append_buffer.buffer.get(..offset.saturating_sub(content_len))?;
+        let line_no = content_before
+        .matches('\n')
+        .count()
+        .saturating_add(buffer_before.matches('\n').count())
+        .saturating_add(1);
+        let line_start = buffer_before
+        .rfind('\n')
+        .map(|newline| content_len.saturating_add(newline))
+        .or(content_before.rfind('\n'))
+        .map_or(0, |newline| newline.saturating_add(1));
+
+        Some((line_no, line_start))
+    }
+
+    // where in the file a line starts. lines past the end
+    // of the file start at the end of it.
+    pub fn line_start(&self, line_no: usize) -> usize {
+        self.content
+        .split('\n')
+        .take(line_no.saturating_sub(1))
+        .map(|line| line.len().saturating_add(1))
+        .sum::<usize>()
+        .min(self.content.len())
+    }
+
+    // the display row that the byte at offset in the file is on
+    pub fn row_at_offset(&self, offset: usize) -> Option<usize> {
+        let (line_no, line_start) = self.line_at_offset(offset)?;
+
+        self.row_of(line_no, offset.saturating_sub(line_start))
+    }
+
+    // the display row that the byte at offset in a line is on
+    pub fn row_of(&self, line_no: usize, offset: usize) -> Option<usize> {
+        let mut index = self.first_row_of_line(line_no)?;
+        let mut row_end = self.content_len_at(index);
+
+        while row_end <= offset
+        && self.line_no_at(index.saturating_add(1)) == line_no {
+            index = index.saturating_add(1);
+            row_end = row_end.saturating_add(self.content_len_at(index));
+        }
+
+        Some(index)
+    }
+
     pub fn insert(&mut self, char: char) {
         self.append_buffer.insert(char);
         self.wrap_buffer();
+        self.update_gutter();
     }
 
     pub fn delete(&mut self) {
         self.append_buffer.delete();
         self.wrap_buffer();
+        self.update_gutter();
+    }
+
+    // the gutter has to fit the biggest line number (or word count),
+    // which changes as lines are written (or backspaced). if the
+    // gutter changes size, there's a different amount of room for
+    // text, so everything gets wrapped again.
+    pub fn update_gutter(&mut self) {
+        self.amt_lines = self.buf_drows
+        .last()
+        .or(self.file_drows.last())
+        .map_or(0, |drow| drow.line_no);
+
+        let gutter_digits = match self.gutter {
+            Gutter::Words => count_digits(
+                self.buf_line_stats
+                .last()
+                .or(self.file_line_stats.last())
+                .map_or(0, |stats| stats.words_before)
+            ),
+            Gutter::Time => TIME_WIDTH,
+            Gutter::Absolute | Gutter::Relative | Gutter::Paragraph => count_digits(self.amt_lines),
+        };
+
+        if gutter_digits != self.gutter_digits {
+            self.gutter_digits = gutter_digits;
+            if self.show_gutter {
+                self.wrap_file();
+                self.wrap_buffer();
+            }
+        }
     }
 
     pub fn display_len(&self) -> usize {
+        // a new or empty file has no row for the buffer to
+        // join onto, so every buffer row is a row of its own
+        if self.file_drows.is_empty() {
+            return self.buf_drows.len()
+        }
         // subtract 1 because the joining row technically exists twice
         if self.buf_drows.len() <= 1 {
             return self.file_drows.len()
@@ -288,7 +569,6 @@ impl Document {
         
         if let Ok(mut file) = OpenOptions::new()
         .read(true)
-        .write(true)
         .append(true)
         .open(&self.metadata.path) {
             if file.seek(std::io::SeekFrom::End(-1)).is_err() {
@@ -308,23 +588,174 @@ impl Document {
     }
 }
 
-pub fn render(to_render: &str) -> String {
+// how many terminal columns a grapheme takes up. outside of the
+// text (like in prompts), tabs are just two spaces, and wide
+// characters (like CJK text and most emoji) take up two
+// columns. a grapheme is a single character on screen, so it
+// can never be wider than that, even if the characters that
+// make it up add up to more.
+pub fn grapheme_width(grapheme: &str) -> usize {
+    if grapheme == "\t" {
+        2
+    } else {
+        grapheme.width().min(2)
+    }
+}
+
+pub fn display_width(to_measure: &str) -> usize {
+    to_measure
+    .graphemes(true)
+    .map(grapheme_width)
+    .sum()
+}
+
+// cuts a string down so it fits in the given number of
+// columns without splitting any graphemes
+pub fn truncate_to_width(to_truncate: &str, max_width: usize) -> String {
+    let mut width = 0;
+
+    to_truncate
+    .graphemes(true)
+    .take_while(|grapheme| {
+        width += grapheme_width(grapheme);
+        width <= max_width
+    })
+    .collect()
+}
+
+// using successors() to compute the number of digits
+// in a number, like the number of lines in the file.
+fn count_digits(number: usize) -> usize {
+    successors(
+        Some(number), 
+        |num| Some(num / 10).filter(|u| *u > 0)
+    ).count()
+}
+
+// how text is drawn in the document, which changes
+// how many columns some of it takes up
+#[derive(Clone, Copy)]
+pub struct Rendering {
+    pub tab_width: usize,
+    // see the glyphs below
+    pub invisibles: bool,
+}
+
+// in the text, a tab goes up to the next tab stop, so how wide
+// it is depends on which column of the row it starts at
+fn tab_stop_width(column: usize, tab_width: usize) -> usize {
+    let tab_width = tab_width.max(1);
+
+    tab_width.saturating_sub(column % tab_width)
+}
+
+// things like zero-width spaces and joiners that take up no
+// room at all. tabs are dealt with separately.
+fn is_zero_width(grapheme: &str) -> bool {
+    grapheme != "\t" && grapheme.width() == 0
+}
+
+// how wide a grapheme in the text is when it starts at column.
+// zero-width characters get a column to themselves when they're
+// shown, so the glyph doesn't cover up the next character.
+fn text_grapheme_width(grapheme: &str, column: usize, rendering: Rendering) -> usize {
+    if grapheme == "\t" {
+        tab_stop_width(column, rendering.tab_width)
+    } else if rendering.invisibles && is_zero_width(grapheme) {
+        1
+    } else {
+        grapheme_width(grapheme)
+    }
+}
+
+// how many columns some text takes up when it starts at column
+pub fn width_at(to_measure: &str, column: usize, rendering: Rendering) -> usize {
+    to_measure
+    .graphemes(true)
+    .fold(column, |at, grapheme| at.saturating_add(text_grapheme_width(grapheme, at, rendering)))
+    .saturating_sub(column)
+}
+
+// turns tabs into spaces up to the next tab stop, and shows the
+// invisible characters if that's on. column is where the text
+// starts on its row. trailing spaces are left for the editor,
+// since it's the one that knows where a line ends.
+pub fn render(to_render: &str, column: usize, rendering: Rendering) -> String {
     let mut rendered = String::new();
+    let mut at = column;
 
     for grapheme in to_render
     .graphemes(true) {
+        let width = text_grapheme_width(grapheme, at, rendering);
+
         if grapheme == "\t" {
-            rendered.push_str("  ");
+            if rendering.invisibles {
+                rendered.push_str(TAB_GLYPH);
+                rendered.push_str(&" ".repeat(width.saturating_sub(1)));
+            } else {
+                rendered.push_str(&" ".repeat(width));
+            }
+        } else if rendering.invisibles && NO_BREAK_SPACES.contains(&grapheme) {
+            rendered.push_str(NO_BREAK_SPACE_GLYPH);
+        } else if rendering.invisibles && is_zero_width(grapheme) {
+            rendered.push_str(ZERO_WIDTH_GLYPH);
         } else {
             rendered.push_str(grapheme);
         }
+        at = at.saturating_add(width);
     }
 
     rendered
 }
 
-// wraps a string to display rows
-pub fn to_display_rows(start_len: usize, start_line_no: usize, to_wrap: &str, max_width: usize) -> Vec<DisplayRow> {
+// how far in the rows after the first one of a line are pushed,
+// so that they line up with the text after a list marker or a
+// block quote's >. lines without either aren't indented, since
+// in prose an indent at the start is usually just a new paragraph.
+fn hanging_indent(line: &str, rendering: Rendering) -> usize {
+    let text = line.trim_start();
+    let leading = line.len().saturating_sub(text.len());
+    let digits = text.chars().take_while(char::is_ascii_digit).count();
+    let mut marker_len = 0;
+
+    if text.starts_with('>') {
+        while text.get(marker_len..).is_some_and(|rest| rest.starts_with('>') || rest.starts_with(' ')) {
+            marker_len = marker_len.saturating_add(1);
+        }
+    } else if ["- ", "* ", "+ "].iter().any(|marker| text.starts_with(marker)) {
+        marker_len = 2;
+    } else if digits > 0
+    && text.get(digits..).is_some_and(|rest| rest.starts_with(". ") || rest.starts_with(") ")) {
+        marker_len = digits.saturating_add(2);
+    }
+
+    if marker_len == 0 {
+        return 0
+    }
+    // list items can have more than one space after the marker
+    let after_marker = text.get(marker_len..).unwrap_or_default();
+    marker_len = marker_len.saturating_add(after_marker.len().saturating_sub(after_marker.trim_start_matches(' ').len()));
+
+    line
+    .get(..leading.saturating_add(marker_len))
+    .map_or(0, |prefix| width_at(prefix, 0, rendering))
+}
+
+// everything to_display_rows() needs to know to lay out text
+#[derive(Clone, Copy)]
+pub struct Wrap {
+    pub max_width: usize,
+    pub hyphenation: Option<Lang>,
+    pub markdown: bool,
+    pub rendering: Rendering,
+    pub hanging_indent: bool,
+}
+
+// wraps a string to display rows. joined_to is the text already
+// on the line the string starts partway through, if any, which
+// decides how far its first line's later rows are indented.
+pub fn to_display_rows(start_len: usize, start_line_no: usize, to_wrap: &str, joined_to: &str, wrap: Wrap) -> Vec<DisplayRow> {
+    let Wrap { max_width, hyphenation, markdown, rendering, .. } = wrap;
     // create vector to return
     let mut display_rows = Vec::new();
 
@@ -334,72 +765,228 @@ pub fn to_display_rows(start_len: usize, start_line_no: usize, to_wrap: &str, ma
         // (the last line no of self.file_drows or 1)
         // to get the current line number.
         let current_line_no = start_line_no.saturating_add(index);
-
-        // count length of the line in graphemes so we know
-        // how long it will actually display as
-        let mut line_display_len = line.graphemes(true).count();
-
+        // where this line's rows start, so they can be styled
+        // once the line has been wrapped
+        let first_row_index = display_rows.len();
         // if we're on the first run of the loop (aka the
-        // display_rows vector is still empty), add the
+        // display_rows vector is still empty), start from the
         // start length so we can display the first line
         // of the buffer correctly
-        if display_rows.is_empty() {
-            line_display_len = line.graphemes(true).count().saturating_add(start_len);
-        }
+        let line_start = if display_rows.is_empty() {
+            start_len
+        } else {
+            0
+        };
+        let whole_line = if index == 0 {
+            format!("{joined_to}{line}")
+        } else {
+            line.to_string()
+        };
+        let indent = if wrap.hanging_indent {
+            hanging_indent(&whole_line, rendering)
+        } else {
+            0
+        };
+        // keep at least half the row for text, however deep
+        // the indent goes
+        let indent = indent.min(max_width / 2);
+
+        // count the width of the line in columns so we know
+        // how long it will actually display as
+        let line_display_len = width_at(line, line_start, rendering).saturating_add(line_start);
 
         // if the line's display length is too wide,
         // start the wrapping process
         if line_display_len >= max_width {
-            // start by splitting the line into chunks by spaces
-            // BAD: should ideally split on something better 
-            // for wide characters/other alphabets later. consider:
-            // let line_chunks = line[..].split_word_bounds().collect::<Vec<&str>>();
-            let line_chunks = line
-            .split_inclusive(' ')
+            // start by splitting the line into chunks wherever unicode
+            // says it's okay to break a line (UAX #14). this is mostly
+            // after spaces, but also after hyphens, dashes and slashes,
+            // and between characters in languages that don't use spaces,
+            // like chinese and japanese. spaces stay at the end of the
+            // chunk before them, like they did when we split on ' '.
+            let mut chunk_start = 0;
+            let line_chunks = linebreaks(line)
+            .filter_map(|(break_index, _)| {
+                let chunk = line.get(chunk_start..break_index);
+                chunk_start = break_index;
+                chunk
+            })
             .collect::<Vec<&str>>();
-            // count display length of chunks and collect them
-            let mut counted_chunks = Vec::new();
-            
-            for chunk in line_chunks {
-                counted_chunks.push((chunk.graphemes(true).count(), chunk));
-            };
 
-            let mut row_display_len = 0;
-            // if we're on the first run of the loop (aka the
-            // display_rows vector is still empty), add the
-            // start length so we can display the first line
-            // of the buffer correctly
-            if display_rows.is_empty() {
-                row_display_len = start_len;
-            }
+            let mut row_display_len = line_start;
+            // the first row of a line isn't indented, but the
+            // rest of them are
+            let mut row_indent = 0;
             let mut row = String::new();
 
-            // iterate over counted chunks 
-            for (chunk_len, chunk) in counted_chunks {
+            // iterate over the chunks. a chunk's width depends on
+            // where it starts if it has tabs in it, so it's counted
+            // again if it gets moved onto a new row.
+            for mut chunk in line_chunks {
+                let mut chunk_len = width_at(chunk, row_display_len, rendering);
                 // if the total row length plus the length of the chunk
                 // if less than the terminal width, add the chunk to the
                 // row
                 if (row_display_len + chunk_len) < max_width {
                     row_display_len = row_display_len.saturating_add(chunk_len);
                     row.push_str(chunk);
-                // otherwise, the combined chunks are a finished row, so push them
-                } else {
-                    display_rows.push(DisplayRow::from((row, row_display_len, current_line_no)));
+                    continue
+                }
+                // if hyphenation is on, see if the start of the word can
+                // still fit on this row. the hyphen is only counted in the
+                // row's length and printed with it, so it never ends up in
+                // the file.
+                if let Some((head, tail)) = hyphenation
+                .and_then(|lang| hyphenate_to_fit(chunk, lang, max_width.saturating_sub(row_display_len))) {
+                    row.push_str(head);
+                    row_display_len = row_display_len
+                    .saturating_add(display_width(head))
+                    .saturating_add(1);
+
+                    let mut hyphenated_row = DisplayRow::from((row, row_display_len, current_line_no));
+                    hyphenated_row.hyphenated = true;
+                    hyphenated_row.indent = row_indent;
+                    display_rows.push(hyphenated_row);
+                    row = String::new();
+                    row_display_len = indent;
+                    row_indent = indent;
+                    chunk = tail;
+                }
+                chunk_len = width_at(chunk, indent, rendering);
+                // otherwise, the combined chunks are a finished row, so push
+                // them. an empty row is only pushed if it's the joining row,
+                // because the buffer always needs a row to start on.
+                if !row.is_empty()
+                || (row_display_len > row_indent && indent.saturating_add(chunk_len) < max_width) {
+                    let mut finished_row = DisplayRow::from((row, row_display_len, current_line_no));
+                    finished_row.indent = row_indent;
+                    display_rows.push(finished_row);
+                    row = String::new();
+                    row_display_len = indent;
+                    row_indent = indent;
+                }
 
+                if row_display_len.saturating_add(chunk_len) < max_width {
                     // reset the row length and row content to be the 
                     // remainder (aka the chunk that would have pushed the
                     // row over the max length)
-                    row_display_len = chunk_len;
+                    row_display_len = row_display_len.saturating_add(chunk_len);
                     row = chunk.to_string();
+                } else {
+                    // the chunk is wider than a whole row on its own (like
+                    // a long url), so break it up between graphemes over as
+                    // many rows as it needs
+                    for grapheme in chunk.graphemes(true) {
+                        let mut grapheme_len = width_at(grapheme, row_display_len, rendering);
+
+                        if row_display_len.saturating_add(grapheme_len) >= max_width {
+                            let mut broken_row = DisplayRow::from((row, row_display_len, current_line_no));
+                            broken_row.broken = true;
+                            broken_row.indent = row_indent;
+                            display_rows.push(broken_row);
+                            row = String::new();
+                            row_display_len = indent;
+                            row_indent = indent;
+                            grapheme_len = width_at(grapheme, row_display_len, rendering);
+                        }
+                        row.push_str(grapheme);
+                        row_display_len = row_display_len.saturating_add(grapheme_len);
+                    }
                 }
             };
             // make sure to push the remainder after the for loop
             // has completed
-            display_rows.push(DisplayRow::from((row, row_display_len, current_line_no)));
+            let mut last_row = DisplayRow::from((row, row_display_len, current_line_no));
+            last_row.indent = row_indent;
+            display_rows.push(last_row);
         } else {
             // if the line isn't too long, just push it directly.
             display_rows.push(DisplayRow::from((line.to_string(), line_display_len, current_line_no)));
         };
+
+        if let Some(line_rows) = display_rows.get_mut(first_row_index..) {
+            finish_line_rows(line_rows, line, &whole_line, markdown);
+        }
     };
     display_rows
 }
+
+// once a line's been wrapped, its rows get what they need to know
+// about the whole line. whole_line is the line with whatever it
+// was joined to in front, if anything.
+fn finish_line_rows(line_rows: &mut [DisplayRow], line: &str, whole_line: &str, markdown: bool) {
+    let rtl = is_rtl_line(whole_line);
+
+    for row in line_rows.iter_mut() {
+        row.rtl = rtl;
+    }
+    if markdown {
+        // the spans are found in the whole line, so emphasis opened
+        // in what it was joined to still closes in this part, then
+        // moved to start from this part
+        let joined_len = whole_line.len().saturating_sub(line.len());
+        let spans: Vec<Span> = line_spans(whole_line)
+        .into_iter()
+        .filter(|span| span.end > joined_len)
+        .map(|span| Span {
+            start: span.start.saturating_sub(joined_len),
+            end: span.end.saturating_sub(joined_len),
+            style: span.style,
+        })
+        .collect();
+
+        style_rows(line_rows, &spans);
+    }
+}
+
+// splits a chunk between syllables so that the first part, plus
+// a hyphen, is shorter than room. only the letters at the start
+// of the chunk get hyphenated, so any punctuation or spaces
+// after the word stay in the second part.
+fn hyphenate_to_fit(chunk: &str, lang: Lang, room: usize) -> Option<(&str, &str)> {
+    let word_len = chunk
+    .char_indices()
+    .find(|(_, character)| !character.is_alphabetic())
+    .map_or(chunk.len(), |(index, _)| index);
+    let word = chunk.get(..word_len)?;
+    let mut head_len: usize = 0;
+    let mut head_width: usize = 0;
+
+    for syllable in hyphenate(word, lang) {
+        let syllable_width = display_width(syllable);
+
+        // the last syllable can't be split off, since that
+        // would just be moving the whole word down anyway
+        if head_len.saturating_add(syllable.len()) >= word_len
+        || head_width.saturating_add(syllable_width).saturating_add(1) >= room {
+            break
+        }
+        head_len = head_len.saturating_add(syllable.len());
+        head_width = head_width.saturating_add(syllable_width);
+    }
+
+    if head_len == 0 {
+        return None
+    }
+    Some((chunk.get(..head_len)?, chunk.get(head_len..)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Document, Metadata, DisplayRow};
+
+    // nothing's been saved to a new file yet, so the buffer's rows
+    // are the only ones there are
+    #[test]
+    fn empty_file_shows_buffer_rows() {
+        let mut document = Document::open(Metadata::get_file_info("", false));
+
+        document.buf_drows = vec![DisplayRow::from((String::from("hello"), 5, 1))];
+        assert_eq!(document.display_len(), 1);
+        assert_eq!(document.get_display_row(0).1.map(|drow| drow.content.as_str()), Some("hello"));
+
+        document.buf_drows.push(DisplayRow::from((String::from("world"), 5, 2)));
+        assert_eq!(document.display_len(), 2);
+        assert_eq!(document.get_display_row(1).1.map(|drow| drow.content.as_str()), Some("world"));
+    }
+}
@@ -1,21 +1,53 @@
-use crate::{die, Terminal, Document, render, Metadata, Config};
+use crate::{die, Terminal, Part, Document, DisplayRow, Extension, Rendering, SPACE_GLYPH, LINE_END_GLYPH, render, width_at, display_width, Style, Span, has_rtl, visual_runs, reverse, grapheme_width, truncate_to_width, Metadata, Config, Progress, Goals, Sprint, TypingStats, SessionRecord, Calendar, Gutter, Search, Match, Bookmark, Picker, PickerItem, read_bookmarks, write_bookmarks, outline, log_sprint, log_session, log_save, read_saves, read_sessions, committed_at, time_label};
 use {
     std::{
         io::Error,
+        ops::Range,
         time::{Duration, Instant},
         env::consts::OS,
         cmp::PartialEq,
     },
-    crossterm::event::{Event, read, poll, KeyEvent, KeyEventKind, KeyModifiers, KeyCode},
+    crossterm::event::{Event, read, poll, KeyEvent, KeyEventKind, KeyModifiers, KeyCode, MouseEvent, MouseEventKind, MouseButton},
+    chrono::Local,
     log::{error, trace},
+    unicode_segmentation::UnicodeSegmentation,
 };
 
 // -----------------
 
 const STANDARD_MESSAGE: &str = "help: press ctrl+h for keybinds";
+// shown on switching to view mode. the keys for it are
+// in the help, since they don't all fit in one message.
+const VIEW_MODE_MESSAGE: &str = "arrow keys, pgup/down and home/end to navigate. ctrl+h - keybinds";
+// ctrl+h shows these a page at a time, so that
+// each one fits on an 80 column terminal
+const EDIT_HELP_PAGES: [&str; 2] = [
+    "ctrl+e - mode | ctrl+s - save | ctrl+o - open | ctrl+q - quit | ctrl+h - more",
+    "ctrl+t - sprint | ctrl+y - calendar | ctrl+l - focus | ctrl+w - invisibles",
+];
+const VIEW_HELP_PAGES: [&str; 4] = [
+    "ctrl+e - mode | ctrl+s - save | ctrl+o - open | ctrl+q - quit | ctrl+h - more",
+    "ctrl+f - search | ctrl+g - go to | ctrl+r - session start | ctrl+h - more",
+    "ctrl+b - bookmark | ctrl+k - bookmarks | ctrl+p - outline | ctrl+h - more",
+    "ctrl+t - sprint | ctrl+y - calendar | ctrl+w - invisibles",
+];
+// shown at the end of a row when a word was too long for it
+const BREAK_MARKER: &str = "↩";
+// shown at the end of a row that ends partway through a word
+const HYPHEN: &str = "-";
+// drawn in place of the > at the start of markdown block quotes
+const QUOTE_BAR: &str = "│";
+// the scrollbar on the right in view mode, when the mouse is on
+const SCROLLBAR_TRACK: &str = "│";
+const SCROLLBAR_THUMB: &str = "█";
+// how many display rows a turn of the mouse wheel scrolls
+const WHEEL_ROWS: usize = 3;
 
 // cursor_pos is only really used if the cursor
 // needs to be placed somewhere special (e.g. in the prompt)
+// the bools are unrelated bits of state, so they can't be
+// rolled up into an enum. see config.rs too.
+#[allow(clippy::struct_excessive_bools)]
 pub struct Editor {
     terminal: Terminal,
     document: Document,
@@ -26,6 +58,26 @@ pub struct Editor {
     mode: Mode,
     quit_times: u8,
     config: Config,
+    progress: Progress,
+    goals: Goals,
+    sprint: Option<Sprint>,
+    typing: TypingStats,
+    // when this is some, the writing calendar covers the screen
+    calendar: Option<Calendar>,
+    // when this is true, only the text being worked on is at
+    // full brightness in edit mode
+    focus: bool,
+    // what was last searched for in view mode
+    search: Search,
+    // how many sessions back ctrl+r last jumped to, where 0 is
+    // the current one. none until it's pressed in view mode.
+    sessions_back: Option<usize>,
+    // when this is some, a list to pick from covers the screen
+    picker: Option<Picker>,
+    // true while the terminal is sending mouse events
+    mouse_captured: bool,
+    // true from a click on the scrollbar until the button is let go
+    scrollbar_drag: bool,
 }
 
 #[derive(Default)]
@@ -41,6 +93,15 @@ enum Direction {
     Right,
 }
 
+// what goes on a row of the screen: a display row, a blank
+// row from line-spacing or paragraph-spacing, or nothing
+#[derive(Clone, Copy)]
+enum ScreenRow {
+    Text(usize),
+    Blank,
+    Empty,
+}
+
 #[derive(PartialEq, Clone)]
 enum Mode {
     View,
@@ -48,6 +109,13 @@ enum Mode {
     Prompt,
 }
 
+// how much of the text focus mode keeps lit up
+#[derive(PartialEq, Clone, Copy)]
+pub enum Focus {
+    Paragraph,
+    Sentence,
+}
+
 #[derive(PartialEq, Clone, Copy)]
 pub enum SaveType {
     Words,
@@ -83,14 +151,23 @@ impl Editor {
             Mode::View
         };
         let quit_times = config.quit_times;
-        let mut document;
+        let document = Editor::load_document(file, &config);
 
-        document = Document::open(file);
-        Document::wrap_file(&mut document);
-        Document::wrap_buffer(&mut document);
+        let progress = Progress::load();
+        let mut goals = Goals::from(&config, &document.content, &document.metadata.extension);
+        goals.init_reached(progress.today(), document.count.words);
         
+        let mut terminal = Terminal::default();
+        terminal.theme = config.theme;
+        let search = Search {
+            ignore_case: config.search_ignore_case,
+            whole_word: config.search_whole_word,
+            regex: config.search_regex,
+            ..Default::default()
+        };
+
         Self {
-            terminal: Terminal::default(),
+            terminal,
             document,
             cursor_pos: Position::default(),
             view_pos: Position::default(),
@@ -99,9 +176,43 @@ impl Editor {
             mode,
             quit_times,
             config,
+            progress,
+            goals,
+            sprint: None,
+            typing: TypingStats::default(),
+            calendar: None,
+            focus: false,
+            search,
+            sessions_back: None,
+            picker: None,
+            mouse_captured: false,
+            scrollbar_drag: false,
         }
     }
 
+    // opens a document and wraps it to fit the layout in the config
+    fn load_document(file: Metadata, config: &Config) -> Document {
+        let mut document = Document::open(file);
+
+        document.text_width = config.text_width;
+        document.show_gutter = config.line_numbers && !config.zen;
+        document.hyphenation = config.hyphenation;
+        document.tab_width = config.tab_width;
+        document.hanging_indent = config.hanging_indent;
+        document.scrollbar = config.mouse;
+        document.markdown = config.render_markdown
+        && document.metadata.extension == Extension::Md;
+        document.gutter = config.gutter;
+        if config.gutter == Gutter::Time {
+            document.save_history = read_saves(&document.metadata.path);
+        }
+        document.wrap_file();
+        document.wrap_buffer();
+        document.update_gutter();
+
+        document
+    }
+
     pub fn run(&mut self) {
         if let Err(error_msg) = Terminal::init() {
             error!("[terminal.rs -> editor.rs]: {error_msg} - could not init terminal.");
@@ -117,21 +228,32 @@ impl Editor {
         self.snap_view();
 
         loop {
+            if self.sprint.as_ref().is_some_and(Sprint::is_over) {
+                if self.document.append_buffer.is_dirty() {
+                    self.save(0, SaveType::Manual);
+                }
+                self.end_sprint();
+            }
+            self.update_mouse_capture();
             if let Err(error_msg) = self.refresh_screen() {
                 error!("[editor.rs]: {error_msg} - could not refresh screen.");
                 die(error_msg);
             };
             if self.should_quit {
+                self.end_sprint();
+                self.record_session();
                 let total_prose = &self.document.count;
                 let session_prose = self.document.written_this_session();
                 let quit_msg = if self.config.count_on_quit {
                     format!(
-                        "goodbye!\r\napprox. total prose in {}:\r\n  {} words\r\n  {} chars\r\nwritten this session:\r\n  {} words\r\n  {} chars\r\n", 
+                        "goodbye!\r\napprox. total prose in {}:\r\n  {} words\r\n  {} chars\r\nwritten this session:\r\n  {} words\r\n  {} chars\r\n{}{}", 
                         self.document.metadata.name,
                         total_prose.words,
                         total_prose.characters,
                         session_prose.words, 
                         session_prose.characters,
+                        self.typing.summary(session_prose.words),
+                        self.goals.summary(self.progress.today(), total_prose.words),
                     )
                 } else {
                     "goodbye!\r\n".to_string()
@@ -151,10 +273,12 @@ impl Editor {
     pub fn refresh_screen(&mut self) -> Result<(), Error> {
         self.terminal.cursor_hide()?;
         self.terminal.move_cursor(&Position::default())?;
+        self.terminal.set_part(Part::Text)?;
         self.draw_rows()?;
         self.draw_status_bar()?;
         self.draw_message_bar()?;
-        if self.mode == Mode::Edit {
+        if self.mode == Mode::Edit
+        && self.calendar.is_none() {
             self.terminal.move_cursor(&Position {
                 x: self.view_pos.x,
                 y: self.terminal.height / 2,
@@ -166,20 +290,117 @@ impl Editor {
     }
 
     pub fn process_event(&mut self) {
-        let event = read();
+        // while a sprint is running, don't wait forever for an
+        // event, so the countdown in the status bar keeps ticking
+        if self.sprint.is_some() {
+            match poll(Duration::from_millis(500)) {
+                Ok(true) => (),
+                Ok(false) => return,
+                Err(error_msg) => error!("[editor.rs::process_event()]: {error_msg} - could not poll."),
+            }
+        }
+        // the mouse moving on its own doesn't change anything,
+        // so it's skipped without going back round to redraw
+        let event = loop {
+            match read() {
+                Ok(Event::Mouse(mouse)) if mouse.kind == MouseEventKind::Moved => (),
+                event => break event,
+            }
+        };
+        let mut was_mouse = matches!(event, Ok(Event::Mouse(_)));
+        self.handle_event(event);
 
+        // the wheel and dragging send lots of events at once, and
+        // only where they end up matters, so everything already
+        // waiting is handled before the screen is redrawn
+        while was_mouse && matches!(poll(Duration::ZERO), Ok(true)) {
+            let event = read();
+            was_mouse = matches!(event, Ok(Event::Mouse(_)));
+            self.handle_event(event);
+        }
+    }
+
+    fn handle_event(&mut self, event: Result<Event, Error>) {
         match event {
             Ok(Event::Key(key)) => if OS == "windows" {
                 self.windows_keypress(key);
             } else {
                 self.process_keypress(key);
             },
+            Ok(Event::Mouse(mouse)) => self.mouse_event(mouse),
             Ok(Event::Resize(first_x, first_y)) => self.term_resize(first_x as usize, first_y as usize),
             Err(error_msg) => error!("[editor.rs::process_event()]: {error_msg} - could not read event."),
             _ => (),
         }
     }
 
+    // the mouse is only captured in view mode, if it's turned on
+    // in the config. see the comment in main.rs.
+    fn update_mouse_capture(&mut self) {
+        let capture = self.config.mouse && self.mode == Mode::View;
+
+        if capture != self.mouse_captured {
+            if let Err(error_msg) = Terminal::set_mouse_capture(capture) {
+                error!("[terminal.rs -> editor.rs]: {error_msg} - could not set mouse capture.");
+            }
+            self.mouse_captured = capture;
+            self.scrollbar_drag = false;
+        }
+    }
+
+    // the wheel scrolls, and clicking or dragging on the
+    // scrollbar jumps to that part of the document
+    fn mouse_event(&mut self, mouse: MouseEvent) {
+        if !self.mouse_captured || self.calendar.is_some() {
+            return
+        }
+        let row = usize::from(mouse.row);
+        let on_scrollbar = usize::from(mouse.column) == self.terminal.width.saturating_sub(1)
+        && row < self.terminal.height;
+
+        match mouse.kind {
+            MouseEventKind::ScrollUp => self.viewing_scroll(&Direction::Up, WHEEL_ROWS),
+            MouseEventKind::ScrollDown => self.viewing_scroll(&Direction::Down, WHEEL_ROWS),
+            MouseEventKind::Down(MouseButton::Left) if on_scrollbar => {
+                self.scrollbar_drag = true;
+                self.scroll_to_screen_row(row);
+            },
+            MouseEventKind::Drag(MouseButton::Left) if self.scrollbar_drag => self.scroll_to_screen_row(row),
+            MouseEventKind::Up(_) => self.scrollbar_drag = false,
+            _ => (),
+        }
+    }
+
+    // the top of the scrollbar is the start of the document
+    // and the bottom is as far as the view can scroll
+    fn scroll_to_screen_row(&mut self, row: usize) {
+        let last_row = self.terminal.height.saturating_sub(1).max(1);
+
+        self.view_pos.y = self.max_view_y()
+        .saturating_mul(row.min(last_row))
+        / last_row;
+    }
+
+    // which rows of the screen the scrollbar's thumb covers,
+    // given how many display rows are on the screen. none if
+    // there's no scrollbar.
+    fn scrollbar_thumb(&self, rows_shown: usize) -> Option<Range<usize>> {
+        if !self.mouse_captured || self.calendar.is_some() || self.picker.is_some() {
+            return None
+        }
+        let term_height = self.terminal.height;
+        let max_view_y = self.max_view_y();
+        let thumb_len = (term_height.saturating_mul(rows_shown) / self.document.display_len().max(1))
+        .clamp(1, term_height.max(1));
+        let thumb_start = term_height
+        .saturating_sub(thumb_len)
+        .saturating_mul(self.view_pos.y.min(max_view_y))
+        .checked_div(max_view_y)
+        .unwrap_or(0);
+
+        Some(thumb_start..thumb_start.saturating_add(thumb_len))
+    }
+
     pub fn term_resize(&mut self, first_x: usize, first_y: usize) {
         let (mut final_x, mut final_y) = (first_x, first_y);
         
@@ -217,7 +438,11 @@ impl Editor {
     }
 
     fn process_keypress(&mut self, key: KeyEvent) {
-        let term_height = self.terminal.height;
+        // any key closes the calendar
+        if self.calendar.is_some() {
+            self.calendar = None;
+            return
+        }
 
         if self.mode != Mode::Prompt {
             match (key.modifiers, key.code) {
@@ -236,15 +461,22 @@ impl Editor {
                 },
                 (KeyModifiers::CONTROL, KeyCode::Char('s')) => self.save(0, SaveType::Manual),
                 (KeyModifiers::CONTROL, KeyCode::Char('o')) => self.open(),
-                (KeyModifiers::CONTROL, KeyCode::Char('h')) => {
-                    self.message = 
-                    Message::from("ctrl+e - mode | ctrl+s - save | ctrl+o - open | ctrl+q - quit".to_string());
-                },
+                (KeyModifiers::CONTROL, KeyCode::Char('t')) => self.toggle_sprint(),
+                (KeyModifiers::CONTROL, KeyCode::Char('y')) => self.open_calendar(),
+                (KeyModifiers::CONTROL, KeyCode::Char('l')) if self.mode == Mode::Edit => self.toggle_focus(),
+                (KeyModifiers::CONTROL, KeyCode::Char('w')) => self.toggle_invisibles(),
+                (KeyModifiers::CONTROL, KeyCode::Char('h')) => self.show_help(),
                 // editing mode
+                (KeyModifiers::CONTROL, KeyCode::Char('e')) if self.mode == Mode::Edit
+                && self.config.sprint_lock
+                && self.sprint.is_some() => {
+                    self.message = Message::from("no peeking! view mode is locked until the sprint is over.".to_string());
+                },
                 (KeyModifiers::CONTROL, KeyCode::Char('e')) if self.mode == Mode::Edit => {
                     self.mode = Mode::View;
+                    self.sessions_back = None;
                     self.message = 
-                    Message::from("arrow keys and pgup/down to navigate.".to_string());
+                    Message::from(VIEW_MODE_MESSAGE.to_string());
                     self.snap_view();
                 },
                 (_, KeyCode::Char(pressed_char)) if self.mode == Mode::Edit => {
@@ -257,6 +489,7 @@ impl Editor {
                     && self.config.save_time > 0 {
                         self.save(0, SaveType::Time);
                     }
+                    self.typing.record(false);
                     self.document.insert(pressed_char);
                     self.snap_view();
                     self.document.last_edit = Instant::now();
@@ -268,6 +501,7 @@ impl Editor {
                     && self.config.save_time > 0 {
                         self.save(0, SaveType::Time);
                     }
+                    self.typing.record(true);
                     if (self.view_pos.x > 0 
                     || self.view_pos.y > 0)
                     && !self.document.append_buffer.buffer.is_empty() {
@@ -286,6 +520,7 @@ impl Editor {
                     && self.config.save_time > 0 {
                         self.save(0, SaveType::Time);
                     }
+                    self.typing.record(false);
                     self.document.insert('\n');
                     self.snap_view();
                     self.document.last_edit = Instant::now();
@@ -293,13 +528,11 @@ impl Editor {
                 // viewing mode
                 (KeyModifiers::CONTROL, KeyCode::Char('e')) if self.mode == Mode::View => {
                     self.mode = Mode::Edit;
+                    self.search.clear();
                     self.message = Message::from(STANDARD_MESSAGE.to_string());
                     self.snap_view();
                 },
-                (_, KeyCode::Up) if self.mode == Mode::View => self.viewing_scroll(&Direction::Up, 1),
-                (_, KeyCode::Down) if self.mode == Mode::View => self.viewing_scroll(&Direction::Down, 1),
-                (_, KeyCode::PageUp) if self.mode == Mode::View => self.viewing_scroll(&Direction::Up, term_height),
-                (_, KeyCode::PageDown) if self.mode == Mode::View => self.viewing_scroll(&Direction::Down, term_height),
+                (modifiers, code) if self.mode == Mode::View => self.view_keypress(modifiers, code),
                 _ => (),
             };
         };
@@ -322,7 +555,12 @@ impl Editor {
     // https://github.com/crossterm-rs/crossterm/issues/752
     // https://github.com/crossterm-rs/crossterm/pull/778
     fn windows_keypress(&mut self, key: KeyEvent) {
-        let term_height = self.terminal.height;
+        if self.calendar.is_some() {
+            if key.kind == KeyEventKind::Press {
+                self.calendar = None;
+            }
+            return
+        }
 
         if self.mode != Mode::Prompt {
             match (key.kind, key.modifiers, key.code) {
@@ -342,15 +580,22 @@ impl Editor {
                 },
                 (KeyEventKind::Press, KeyModifiers::CONTROL, KeyCode::Char('s')) => self.save(0, SaveType::Manual),
                 (KeyEventKind::Press, KeyModifiers::CONTROL, KeyCode::Char('o')) => self.open(),
-                (KeyEventKind::Press, KeyModifiers::CONTROL, KeyCode::Char('h')) => {
-                    self.message = 
-                    Message::from("ctrl+e - mode | ctrl+s - save | ctrl+o - open | ctrl+q - quit".to_string());
-                },
+                (KeyEventKind::Press, KeyModifiers::CONTROL, KeyCode::Char('t')) => self.toggle_sprint(),
+                (KeyEventKind::Press, KeyModifiers::CONTROL, KeyCode::Char('y')) => self.open_calendar(),
+                (KeyEventKind::Press, KeyModifiers::CONTROL, KeyCode::Char('l')) if self.mode == Mode::Edit => self.toggle_focus(),
+                (KeyEventKind::Press, KeyModifiers::CONTROL, KeyCode::Char('w')) => self.toggle_invisibles(),
+                (KeyEventKind::Press, KeyModifiers::CONTROL, KeyCode::Char('h')) => self.show_help(),
                 // editing mode
+                (KeyEventKind::Press, KeyModifiers::CONTROL, KeyCode::Char('e')) if self.mode == Mode::Edit
+                && self.config.sprint_lock
+                && self.sprint.is_some() => {
+                    self.message = Message::from("no peeking! view mode is locked until the sprint is over.".to_string());
+                },
                 (KeyEventKind::Press, KeyModifiers::CONTROL, KeyCode::Char('e')) if self.mode == Mode::Edit => {
                     self.mode = Mode::View;
+                    self.sessions_back = None;
                     self.message = 
-                    Message::from("arrow keys and pgup/down to navigate.".to_string());
+                    Message::from(VIEW_MODE_MESSAGE.to_string());
                     self.snap_view();
                 },
                 (KeyEventKind::Press, _, KeyCode::Char(pressed_char)) if self.mode == Mode::Edit => {
@@ -363,6 +608,7 @@ impl Editor {
                     && self.config.save_time > 0 {
                         self.save(0, SaveType::Time);
                     }
+                    self.typing.record(false);
                     self.document.insert(pressed_char);
                     self.snap_view();
                     self.document.last_edit = Instant::now();
@@ -375,6 +621,7 @@ impl Editor {
                         self.save(0, SaveType::Time);
                         self.message = Message::from("sorry, five seconds passed! file saved.".to_string());
                     }
+                    self.typing.record(true);
                     if (self.view_pos.x > 0 
                     || self.view_pos.y > 0)
                     && !self.document.append_buffer.buffer.is_empty() {
@@ -393,6 +640,7 @@ impl Editor {
                     && self.config.save_time > 0{
                         self.save(0, SaveType::Time);
                     }
+                    self.typing.record(false);
                     self.document.insert('\n');
                     self.snap_view();
                     self.document.last_edit = Instant::now();
@@ -400,63 +648,91 @@ impl Editor {
                 // viewing mode
                 (KeyEventKind::Press, KeyModifiers::CONTROL, KeyCode::Char('e')) if self.mode == Mode::View => {
                     self.mode = Mode::Edit;
+                    self.search.clear();
                     self.message = Message::from(STANDARD_MESSAGE.to_string());
                     self.snap_view();
                 },
-                (KeyEventKind::Press, _, KeyCode::Up) if self.mode == Mode::View => self.viewing_scroll(&Direction::Up, 1),
-                (KeyEventKind::Press, _, KeyCode::Down) if self.mode == Mode::View => self.viewing_scroll(&Direction::Down, 1),
-                (KeyEventKind::Press, _, KeyCode::PageUp) if self.mode == Mode::View => self.viewing_scroll(&Direction::Up, term_height),
-                (KeyEventKind::Press, _, KeyCode::PageDown) if self.mode == Mode::View => self.viewing_scroll(&Direction::Down, term_height),
+                (KeyEventKind::Press, modifiers, code) if self.mode == Mode::View => self.view_keypress(modifiers, code),
                 _ => (),
             };
         };
     }
 
+    // the keys that only do something in view mode. both keypress
+    // functions send them here, so there's only one copy of these.
+    fn view_keypress(&mut self, modifiers: KeyModifiers, code: KeyCode) {
+        let vi_keys = self.config.vi_keys;
+        let half_page = (self.terminal.height / 2).max(1);
+
+        match (modifiers, code) {
+            (KeyModifiers::CONTROL, KeyCode::Up) => self.page_scroll(&Direction::Up, half_page),
+            (KeyModifiers::CONTROL, KeyCode::Down) => self.page_scroll(&Direction::Down, half_page),
+            (_, KeyCode::Up) => self.viewing_scroll(&Direction::Up, 1),
+            (_, KeyCode::Down) => self.viewing_scroll(&Direction::Down, 1),
+            (_, KeyCode::PageUp) => self.page_scroll(&Direction::Up, self.terminal.height),
+            (_, KeyCode::PageDown) => self.page_scroll(&Direction::Down, self.terminal.height),
+            (_, KeyCode::Home) => self.view_pos.y = 0,
+            (_, KeyCode::End) => self.view_pos.y = self.max_view_y(),
+            (KeyModifiers::CONTROL, KeyCode::Char('f')) => self.search(),
+            (KeyModifiers::NONE, KeyCode::Char('n')) => self.next_match(true),
+            (KeyModifiers::NONE | KeyModifiers::SHIFT, KeyCode::Char('N')) => self.next_match(false),
+            (KeyModifiers::CONTROL, KeyCode::Char('g')) => self.go_to(),
+            (KeyModifiers::CONTROL, KeyCode::Char('r')) => self.jump_to_session(),
+            (KeyModifiers::CONTROL, KeyCode::Char('b')) => self.add_bookmark(),
+            (KeyModifiers::CONTROL, KeyCode::Char('k')) => self.open_bookmarks(),
+            (KeyModifiers::CONTROL, KeyCode::Char('p')) => self.open_outline(),
+            // like less, g and G go straight to the top and bottom
+            (KeyModifiers::NONE, KeyCode::Char('j')) if vi_keys => self.viewing_scroll(&Direction::Down, 1),
+            (KeyModifiers::NONE, KeyCode::Char('k')) if vi_keys => self.viewing_scroll(&Direction::Up, 1),
+            (KeyModifiers::NONE, KeyCode::Char('g')) if vi_keys => self.view_pos.y = 0,
+            (KeyModifiers::NONE | KeyModifiers::SHIFT, KeyCode::Char('G')) if vi_keys => self.view_pos.y = self.max_view_y(),
+            (KeyModifiers::CONTROL, KeyCode::Char('d')) if vi_keys => self.page_scroll(&Direction::Down, half_page),
+            (KeyModifiers::CONTROL, KeyCode::Char('u')) if vi_keys => self.page_scroll(&Direction::Up, half_page),
+            _ => (),
+        };
+    }
+
     // snap view so end of file is at the middle of the screen
     // if in editing mode, also snap cursor to end of line
     fn snap_view(&mut self) {
-        let term_height = self.terminal.height;
-        let max_height = self.document.display_len();
-        let last_display_index = max_height.saturating_sub(1);
-        let gutter_len = self.document.line_no_digits.saturating_add(1);
+        let last_display_index = self.document.display_len().saturating_sub(1);
+        // the cursor sits after the margin and the gutter
+        let text_start = self.left_margin().saturating_add(self.document.gutter_width());
 
         if self.mode == Mode::Edit {
             self.view_pos.y = last_display_index;
-            // we should always be in the buffer in edit mode,
-            // so it should be safe to just get a buffer drow
-            // at the last index here
-            if let Some(last_buf_drow) = self.document.buf_drows.last() {
-                if self.config.line_numbers {
-                    self.view_pos.x = last_buf_drow.len.saturating_add(gutter_len);
-                } else {
-                    self.view_pos.x = last_buf_drow.len;
-                };
-            } else if let Some(last_file_drow) = self.document.file_drows.last() {
-                if self.config.line_numbers {
-                    self.view_pos.x = last_file_drow.len.saturating_add(gutter_len);
-                } else {
-                    self.view_pos.x = last_file_drow.len;
-                };
-            } else {
+            // we should always be at the end of the buffer in edit
+            // mode, which is on the left of right-to-left text
+            if self.document.buf_drows.is_empty() && self.document.file_drows.is_empty() {
                 trace!("no last row");
-                if self.config.line_numbers {
-                    self.view_pos.x = 0_usize.saturating_add(gutter_len);
-                } else {
-                    self.view_pos.x = 0;
-                };
+                self.view_pos.x = text_start;
+            } else {
+                self.view_pos.x = self.document.append_column().saturating_add(text_start);
             };
         } else if self.mode == Mode::View {
-            if self.view_pos.y > max_height.saturating_sub((term_height / 2).saturating_add(1)) {
-                self.view_pos.y = max_height.saturating_sub((term_height / 2).saturating_add(1));
+            if self.view_pos.y > self.max_view_y() {
+                self.view_pos.y = self.max_view_y();
             };
-        } else if self.view_pos.y > max_height.saturating_sub((term_height / 2).saturating_add(1)) {
-            self.view_pos.y = max_height.saturating_sub((term_height / 2).saturating_add(1));
+        } else if self.view_pos.y > self.max_view_y() {
+            self.view_pos.y = self.max_view_y();
         };
     }
 
+    // how far the text is pushed in from the left edge so it
+    // sits in the middle of the window when centered
+    fn left_margin(&self) -> usize {
+        if self.config.center_text && self.config.text_width > 0 {
+            self.terminal.width
+            .saturating_sub(self.document.gutter_width())
+            .saturating_sub(self.document.wrap_width())
+            / 2
+        } else {
+            0
+        }
+    }
+
     fn viewing_scroll(&mut self, direction: &Direction, amount: usize) {
-        let max_height = self.document.display_len();
-        let term_height = self.terminal.height;
+        let max_view_y = self.max_view_y();
         let position = &mut self.view_pos;
 
         match direction {
@@ -466,258 +742,400 @@ impl Editor {
         };
 
         // stop view from scrolling too far past the end of the file
-        if position.y > max_height.saturating_sub((term_height / 2).saturating_add(1)) {
-            position.y = max_height.saturating_sub((term_height / 2).saturating_add(1));
+        if position.y > max_view_y {
+            position.y = max_view_y;
         };
     }
 
+    // scrolls by however many display rows fit in height rows of
+    // the screen, which is fewer than that with spacing on
+    fn page_scroll(&mut self, direction: &Direction, height: usize) {
+        let display_len = self.document.display_len();
+        let mut index = self.view_pos.y;
+        let mut used: usize = 0;
+        let mut amount: usize = 0;
+
+        loop {
+            let next = match direction {
+                Direction::Up => index.checked_sub(1),
+                _ => Some(index.saturating_add(1)).filter(|next| *next < display_len),
+            };
+            let Some(next) = next else {
+                break
+            };
+            // rows going up are measured from the row above,
+            // and rows going down from the row being left
+            let taken = match direction {
+                Direction::Up => self.spacing_after(next),
+                _ => self.spacing_after(index),
+            }
+            .saturating_add(1);
+
+            if used.saturating_add(taken) > height {
+                break
+            }
+            used = used.saturating_add(taken);
+            amount = amount.saturating_add(1);
+            index = next;
+        }
+
+        self.viewing_scroll(direction, amount.max(1));
+    }
+
+    // the view can scroll until the last row is in the
+    // middle of the screen, but no further
+    fn max_view_y(&self) -> usize {
+        let half_height = self.terminal.height / 2;
+        let mut index = self.document.display_len().saturating_sub(1);
+        let mut used: usize = 0;
+
+        while let Some(previous) = index.checked_sub(1) {
+            let taken = self.spacing_after(previous).saturating_add(1);
+
+            if used.saturating_add(taken) > half_height {
+                break
+            }
+            used = used.saturating_add(taken);
+            index = previous;
+        }
+
+        index
+    }
+
+    // how many blank rows go under the display row at index.
+    // the last row of a line ends a paragraph.
+    fn spacing_after(&self, index: usize) -> usize {
+        let line_spacing = self.config.line_spacing.saturating_sub(1);
+
+        if self.document.line_no_at(index.saturating_add(1)) == self.document.line_no_at(index) {
+            line_spacing
+        } else {
+            line_spacing.saturating_add(self.config.paragraph_spacing)
+        }
+    }
+
+    // works out what goes on each row of the screen. in edit mode,
+    // the row being written on sits in the middle, so the rows
+    // above it are filled in going backwards from there.
+    fn screen_rows(&self) -> Vec<ScreenRow> {
+        let term_height = self.terminal.height;
+        let display_len = self.document.display_len();
+        let mut rows = Vec::new();
+
+        if self.mode != Mode::View {
+            let editing_offset = term_height / 2;
+            let mut above = Vec::new();
+            let mut index = self.view_pos.y;
+
+            while above.len() < editing_offset {
+                if let Some(previous) = index.checked_sub(1) {
+                    index = previous;
+                    above.extend((0..self.spacing_after(index)).map(|_| ScreenRow::Blank));
+                    above.push(ScreenRow::Text(index));
+                } else {
+                    above.push(ScreenRow::Empty);
+                }
+            }
+            above.truncate(editing_offset);
+            rows.extend(above.into_iter().rev());
+        }
+
+        let mut index = self.view_pos.y;
+        while rows.len() < term_height {
+            if index < display_len {
+                rows.push(ScreenRow::Text(index));
+                rows.extend((0..self.spacing_after(index)).map(|_| ScreenRow::Blank));
+                index = index.saturating_add(1);
+            } else {
+                rows.push(ScreenRow::Empty);
+            }
+        }
+        rows.truncate(term_height);
+
+        rows
+    }
+
     fn draw_rows(&mut self) -> Result<(), Error> {
         let term_height = self.terminal.height;
-        let editing_offset = self.terminal.height / 2;
         let mut last_line_no = 0;
 
-        for term_row in 0..term_height {
-            // draw rows in view mode
-            if self.mode == Mode::View {
-                let index_to_display = self.view_pos.y.saturating_add(term_row);
+        // the calendar and pickers cover the whole screen
+        let overlay = if let Some(calendar) = &self.calendar {
+            Some(calendar.lines(self.terminal.width))
+        } else {
+            self.picker
+            .as_ref()
+            .map(|picker| picker.lines(self.terminal.width, term_height))
+        };
+        if let Some(overlay_lines) = overlay {
+            for term_row in 0..term_height {
+                if let Some(line) = overlay_lines.get(term_row) {
+                    self.terminal.queue_print(line)?;
+                }
+                self.terminal.clear_line()?;
+                self.terminal.new_line()?;
+            }
+            return Ok(())
+        }
 
+        let margin = " ".repeat(self.left_margin());
+        let focus_start = if self.focus && self.mode == Mode::Edit {
+            Some(self.focus_start())
+        } else {
+            None
+        };
+
+        let screen_rows = self.screen_rows();
+        let rows_shown = screen_rows
+        .iter()
+        .filter(|screen_row| matches!(screen_row, ScreenRow::Text(_)))
+        .count();
+        let thumb = self.scrollbar_thumb(rows_shown);
+        let scrollbar_x = self.terminal.width.saturating_sub(1);
+
+        for (term_row, screen_row) in screen_rows.into_iter().enumerate() {
+            self.terminal.queue_print(&margin)?;
+            match screen_row {
                 // since print_row() returns the line number of the row it's working on
                 // i can set last_line_no and print the row at the same time
-                last_line_no = self.print_row(index_to_display, last_line_no)?;
-
-                // match self.document.get_display_row(index_to_display) {
-                //     (Some(file_drow), None) => {
-                //         let file_content = render(&file_drow.content);
-                //         let to_print = format!(" {file_content}");
-                //         if last_line_no == file_drow.line_no {
-                //             gutter = " ".repeat(line_no_digits);
-                //             self.terminal.queue_print_reversed(&gutter)?;
-                //         } else {
-                //             gutter = format!("{:>line_no_digits$}", file_drow.line_no.to_string());
-                //             self.terminal.queue_print_reversed(&gutter)?;
-                //         }
-                //         last_line_no = file_drow.line_no;
-                //         self.terminal.queue_print(&to_print)?;
-                //     },
-                //     (None, Some(buf_drow)) => {
-                //         let buf_content = render(&buf_drow.content);
-                //         let to_print = format!(" {buf_content}");
-                //         if last_line_no == buf_drow.line_no {
-                //             gutter = " ".repeat(line_no_digits);
-                //             self.terminal.queue_print_reversed(&gutter)?;
-                //         } else {
-                //             gutter = format!("{:>line_no_digits$}", buf_drow.line_no.to_string());
-                //             self.terminal.queue_print_reversed(&gutter)?;
-                //         }
-                //         last_line_no = buf_drow.line_no;
-                //         self.terminal.queue_print(&to_print)?;
-                //     },
-                //     (Some(file_drow), Some(buf_drow)) => {
-                //         let file_content = render(&file_drow.content);
-                //         let buf_content = render(&buf_drow.content);
-                //         let to_print = format!(" {file_content}{buf_content}");
-                //         if last_line_no == file_drow.line_no {
-                //             let gutter = " ".repeat(self.document.line_no_digits);
-                //             self.terminal.queue_print_reversed(&gutter)?;
-                //         } else {
-                //             self.terminal.queue_print_reversed(&file_drow.line_no.to_string())?;
-                //         }
-                //         last_line_no = file_drow.line_no;
-                //         self.terminal.queue_print(&render(&to_print))?;
-                //     },
-                //     (None, None) => {
-                //         let gutter = " ".repeat(self.document.line_no_digits);
-                //         self.terminal.queue_print_reversed(&gutter)?;
-                //         self.terminal.queue_print(" ~")?;
-                //     },
-                // };
-            // draw rows in edit/prompt mode
-            // by checking if ((view position's y + current term row) - editing offset) 
-            // does not overflow (read: would not become less than zero), we can make sure
-            // to keep the view in the middle of the screen and print '~' before it
-            } else if let Some(index_to_display) = self.view_pos.y.saturating_add(term_row).checked_sub(editing_offset) {
-                // since print_row() returns the line number of the row it's working on
-                // i can set last_line_no and print the row at the same time
-                last_line_no = self.print_row(index_to_display, last_line_no)?;
-
-                // match self.document.get_display_row(index_to_display) {
-                //     (Some(file_drow), None) => {
-                //         let file_content = render(&file_drow.content);
-                //         let to_print = format!(" {file_content}");
-                //         if last_line_no == file_drow.line_no {
-                //             gutter = " ".repeat(line_no_digits);
-                //             self.terminal.queue_print_reversed(&gutter)?;
-                //         } else {
-                //             gutter = format!("{:>line_no_digits$}", file_drow.line_no.to_string());
-                //             self.terminal.queue_print_reversed(&gutter)?;
-                //         }                      
-                //         self.terminal.queue_print(&to_print)?;
-                //     },
-                //     (None, Some(buf_drow)) => {
-                //         if buf_drow.content.is_empty() {
-                //             self.terminal.queue_print_reversed(" ")?;
-                //         } else {
-                //             self.terminal.reverse_colors()?;
-                //             self.terminal.queue_print(&render(&buf_drow.content))?;
-                //             self.terminal.no_reverse_colors()?;
-                //         }
-                //     },
-                //     (Some(file_drow), Some(buf_drow)) => {
-                //         self.terminal.queue_print(&render(&file_drow.content))?;
-                //         self.terminal.reverse_colors()?;
-                //         self.terminal.queue_print(&render(&buf_drow.content))?;
-                //         self.terminal.no_reverse_colors()?;
-                //     },
-                //     (None, None) => {
-                //         let gutter = " ".repeat(self.document.line_no_digits);
-                //         self.terminal.queue_print_reversed(&gutter)?;
-                //         self.terminal.queue_print(" ~")?;
-                //     },
-                // };
-            } else {
-                if self.config.line_numbers {
-                    let gutter = " ".repeat(self.document.line_no_digits);
-                    self.terminal.queue_print_reversed(&gutter)?;
-                    self.terminal.queue_print(" ")?;
-                }
-                self.terminal.queue_print("~")?;
+                ScreenRow::Text(index_to_display) => {
+                    last_line_no = self.print_row(index_to_display, last_line_no, dimmed_len(focus_start, index_to_display))?;
+                },
+                // spacing rows keep the gutter going, but the rows
+                // above and below the file get a ~
+                ScreenRow::Blank | ScreenRow::Empty => {
+                    if self.document.show_gutter {
+                        let gutter = " ".repeat(self.document.gutter_digits);
+                        self.terminal.queue_print_as(&gutter, Part::Gutter)?;
+                        self.terminal.queue_print(" ")?;
+                    }
+                    if matches!(screen_row, ScreenRow::Empty) {
+                        self.terminal.queue_print("~")?;
+                    }
+                },
             }
             self.terminal.clear_line()?;
+            match &thumb {
+                Some(thumb) if thumb.contains(&term_row) => self.terminal.queue_print_at(SCROLLBAR_THUMB, scrollbar_x, Part::Text)?,
+                Some(_) => self.terminal.queue_print_at(SCROLLBAR_TRACK, scrollbar_x, Part::Gutter)?,
+                None => (),
+            }
             self.terminal.new_line()?;
         }
         Ok(())
     }
 
-    fn print_row(&mut self, row_index: usize, last_line_no: usize) -> Result<usize, Error> {
-        let line_no_digits = self.document.line_no_digits;
-        let gutter;
+    // dimmed is how many bytes at the start of the row are
+    // dimmed by focus mode
+    fn print_row(&mut self, row_index: usize, last_line_no: usize, dimmed: usize) -> Result<usize, Error> {
+        // markdown styling is only shown in view mode
+        let styled = self.mode == Mode::View;
+        let dim_markers = self.config.dim_markers;
+        let rendering = self.document.rendering();
+        let break_marker = self.config.break_marker;
+        // the last row of a line is where its trailing spaces are,
+        // and it ends in a line break unless it's the last line
+        let line_no = self.document.line_no_at(row_index);
+        let next_line_no = self.document.line_no_at(row_index.saturating_add(1));
+        let line_ends = rendering.invisibles && next_line_no != line_no;
+        let line_break = line_ends && next_line_no != 0;
+
+        if self.document.show_gutter {
+            let gutter = self.gutter_label(line_no, last_line_no);
+            self.terminal.queue_print_as(&gutter, Part::Gutter)?;
+            self.terminal.queue_print(" ")?;
+        }
+
+        // rows with right-to-left text or search matches in them
+        // are drawn a piece at a time, in the order they're shown in
+        let (file_drow, buf_drow) = self.document.get_display_row(row_index);
+        let highlights = self.highlights(row_index, line_no);
+        if let Some(last_drow) = buf_drow.or(file_drow)
+        .filter(|_| !highlights.is_empty() || [file_drow, buf_drow].iter().flatten().any(|drow| has_rtl(&drow.content))) {
+            let buffer_part = if styled {
+                Part::Text
+            } else {
+                Part::Buffer
+            };
+            let (row, pieces) = row_pieces(file_drow, buf_drow, styled, dimmed, line_ends, buffer_part, &highlights);
+            let indent = file_drow.or(buf_drow).map_or(0, |drow| drow.indent);
+
+            self.terminal.queue_print(&" ".repeat(indent))?;
+            print_pieces(&mut self.terminal, &row, &pieces, last_drow.rtl, indent, rendering, dim_markers)?;
+            print_row_end(&mut self.terminal, last_drow, break_marker, line_break)?;
+            return Ok(line_no)
+        }
 
         match self.document.get_display_row(row_index) {
             (Some(file_drow), None) => {
-                let file_content = render(&file_drow.content);
-
-                if self.config.line_numbers {
-                    if last_line_no == file_drow.line_no {
-                        gutter = " ".repeat(line_no_digits);
-                        self.terminal.queue_print_reversed(&gutter)?;
-                    } else {
-                        gutter = format!("{:>line_no_digits$}", file_drow.line_no.to_string());
-                        self.terminal.queue_print_reversed(&gutter)?;
-                    };
-                    self.terminal.queue_print(" ")?;
-                };
-                
-                self.terminal.queue_print(&file_content)?;
+                let trailing = trailing_from(&file_drow.content, line_ends);
+
+                self.terminal.queue_print(&" ".repeat(file_drow.indent))?;
+                if styled {
+                    print_content(&mut self.terminal, file_drow, file_drow.indent, styled, dim_markers, rendering, trailing)?;
+                } else {
+                    print_focused(&mut self.terminal, &file_drow.content, dimmed, Part::Text, file_drow.indent, rendering, trailing)?;
+                }
+                print_row_end(&mut self.terminal, file_drow, break_marker, line_break)?;
                 Ok(file_drow.line_no)
             },
             (None, Some(buf_drow)) => {
-                let buf_content = render(&buf_drow.content);
-
-                if self.config.line_numbers {
-                    if last_line_no == buf_drow.line_no {
-                        gutter = " ".repeat(line_no_digits);
-                        self.terminal.queue_print_reversed(&gutter)?;
-                    } else {
-                        gutter = format!("{:>line_no_digits$}", buf_drow.line_no.to_string());
-                        self.terminal.queue_print_reversed(&gutter)?;
-                    };
-                    self.terminal.queue_print(" ")?;
-                };
+                let trailing = trailing_from(&buf_drow.content, line_ends);
+
+                self.terminal.queue_print(&" ".repeat(buf_drow.indent))?;
                 if self.mode == Mode::View {
-                    self.terminal.queue_print(&buf_content)?;
-                } else if buf_content.is_empty() {
-                    self.terminal.queue_print_reversed(" ")?;
+                    print_content(&mut self.terminal, buf_drow, buf_drow.indent, styled, dim_markers, rendering, trailing)?;
+                } else if buf_drow.content.is_empty() {
+                    self.terminal.queue_print_as(" ", Part::Buffer)?;
                 } else {
-                    self.terminal.queue_print_reversed(&buf_content)?;
+                    print_focused(&mut self.terminal, &buf_drow.content, dimmed, Part::Buffer, buf_drow.indent, rendering, trailing)?;
                 }
+                print_row_end(&mut self.terminal, buf_drow, break_marker, line_break)?;
                 Ok(buf_drow.line_no)
             },
+            // the buffer carries on from the end of the file's row,
+            // so the file's spaces are only trailing if the buffer
+            // doesn't have anything but spaces after them
             (Some(file_drow), Some(buf_drow)) => {
-                let file_content = render(&file_drow.content);
-                let buf_content = render(&buf_drow.content);
-
-                if self.config.line_numbers {
-                    if last_line_no == file_drow.line_no {
-                        let gutter = " ".repeat(self.document.line_no_digits);
-                        self.terminal.queue_print_reversed(&gutter)?;
-                    } else {
-                        self.terminal.queue_print_reversed(&file_drow.line_no.to_string())?;
-                    }
-                    self.terminal.queue_print(" ")?;
-                }
-                self.terminal.queue_print(&file_content)?;
+                let buf_trailing = trailing_from(&buf_drow.content, line_ends);
+                let file_trailing = trailing_from(&file_drow.content, line_ends && buf_trailing == 0);
+
+                self.terminal.queue_print(&" ".repeat(file_drow.indent))?;
                 if self.mode == Mode::View {
-                    self.terminal.queue_print(&buf_content)?;
+                    print_content(&mut self.terminal, file_drow, file_drow.indent, styled, dim_markers, rendering, file_trailing)?;
+                    print_content(&mut self.terminal, buf_drow, file_drow.len, styled, dim_markers, rendering, buf_trailing)?;
                 } else {
-                    self.terminal.queue_print_reversed(&buf_content)?;
+                    print_focused(&mut self.terminal, &file_drow.content, dimmed, Part::Text, file_drow.indent, rendering, file_trailing)?;
+                    print_focused(
+                        &mut self.terminal,
+                        &buf_drow.content,
+                        dimmed.saturating_sub(file_drow.content.len()),
+                        Part::Buffer,
+                        file_drow.len,
+                        rendering,
+                        buf_trailing,
+                    )?;
                 }
+                print_row_end(&mut self.terminal, buf_drow, break_marker, line_break)?;
                 Ok(file_drow.line_no)
             },
             (None, None) => {
-                if self.config.line_numbers {
-                    let gutter = " ".repeat(self.document.line_no_digits);
-                    self.terminal.queue_print_reversed(&gutter)?;
-                    self.terminal.queue_print(" ")?;
-                }
                 self.terminal.queue_print("~")?;
                 Ok(0)
             },
         }
     }
 
+    // continuation rows and rows past the end of the
+    // file (line 0) get an empty gutter
+    fn gutter_label(&self, line_no: usize, last_line_no: usize) -> String {
+        let gutter_digits = self.document.gutter_digits;
+
+        if line_no == 0 || line_no == last_line_no {
+            return " ".repeat(gutter_digits)
+        }
+        let stats = self.document.stats_for_line(line_no);
+        let label = match self.document.gutter {
+            Gutter::Absolute => line_no.to_string(),
+            // the line being viewed keeps its own number, like in vim
+            Gutter::Relative => {
+                let current_line_no = if self.mode == Mode::View {
+                    self.document.line_no_at(self.view_pos.y)
+                } else {
+                    self.document.amt_lines
+                };
+
+                if line_no == current_line_no {
+                    line_no.to_string()
+                } else {
+                    line_no.abs_diff(current_line_no).to_string()
+                }
+            },
+            Gutter::Paragraph => stats
+            .filter(|stats| !stats.blank)
+            .map_or(String::new(), |stats| stats.paragraph.to_string()),
+            Gutter::Words => stats.map_or(String::new(), |stats| stats.words_before.to_string()),
+            Gutter::Time => stats
+            .and_then(|stats| committed_at(&self.document.save_history, stats.offset))
+            .map_or(String::new(), time_label),
+        };
+
+        format!("{label:>gutter_digits$}")
+    }
+
     fn draw_status_bar(&mut self) -> Result<(), Error>{
+        // leave the row empty in zen so the text doesn't move around
+        if self.config.zen {
+            self.terminal.clear_line()?;
+            self.terminal.new_line()?;
+            return Ok(())
+        }
         // BAD: dividing this by three leads to the formatting getting screwed up
         // when the width isn't evenly divisible by three
         // let words = self.document.count.words;
         // let chars = self.document.count.chars;
-        let mut file_name = self.document.metadata.name.clone();
+        let file_name = &self.document.metadata.name;
         let dirty_indicator = if self.document.append_buffer.is_dirty() {
             "(*)"
         } else {
             ""
         };
         let mode_indicator = match self.mode {
-            Mode::Edit => "EDITING",
-            Mode::Prompt => "",
-            Mode::View => "VIEWING",
+            Mode::Edit => "EDITING".to_string(),
+            Mode::Prompt => String::new(),
+            Mode::View if self.search.active => format!("{}VIEWING", self.search.options()),
+            Mode::View => "VIEWING".to_string(),
         };
         // let count_indicator = format!(
         //     "{words} words / {chars} chars"
         // );
+        let sprint_indicator = self.sprint.as_ref().map_or(String::new(), Sprint::countdown);
+        let goal_indicator = format!(
+            "{sprint_indicator}{}",
+            self.goals.indicator(self.progress.today(), self.document.count.words),
+        );
 
-        let file_indicator = format!("{file_name} {dirty_indicator}");
+        let max_width = self.terminal.width
+        .saturating_sub(mode_indicator.len())
+        .saturating_sub(goal_indicator.len());
 
         // BAD?: give some indication if the file name has been truncated?
-        file_name.truncate(20);
-
-        let max_width = self.terminal.width.saturating_sub(mode_indicator.len());
+        let file_indicator = truncate_to_width(&format!("{file_name} {dirty_indicator}"), max_width);
+        // pad by columns rather than characters, since the
+        // file name could have wide characters in it
+        let padding = " ".repeat(max_width.saturating_sub(display_width(&file_indicator)));
 
         let status_bar = format!(
-            "{file_indicator:<max_width$}{mode_indicator}"
+            "{file_indicator}{padding}{goal_indicator}{mode_indicator}"
         );
 
-        self.terminal.reverse_colors()?;
-        self.terminal.queue_print(&status_bar)?;
-        self.terminal.no_reverse_colors()?;
+        self.terminal.queue_print_as(&status_bar, Part::StatusBar)?;
         self.terminal.new_line()?;
         Ok(())
     }
 
     fn draw_message_bar(&mut self) -> Result<(), Error> {
+        self.terminal.set_part(Part::Message)?;
         self.terminal.clear_line()?;
 
         let message = &self.message;
 
         if message.time.elapsed() < Duration::new(5, 0) {
-            let mut text = message.text.clone();
+            let text = truncate_to_width(&message.text, self.terminal.width);
 
-            text.truncate(self.terminal.width);
             self.terminal.queue_print(&text)?;
         }
+        self.terminal.set_part(Part::Text)?;
         Ok(())
     }
 
     pub fn save(&mut self, words: u8, save_type: SaveType) {
+        let words_before = self.document.count.words;
+        let gutter_before = self.document.gutter_digits;
+        let top_line_no = self.document.line_no_at(self.view_pos.y);
+
+        let saves_before = self.document.save_history.len();
+
         match self.document.save(words, save_type) {
             Ok(()) if save_type == SaveType::Manual => self.message = Message::from("file saved successfully.".to_string()),
             Ok(()) => (),
@@ -726,6 +1144,188 @@ impl Editor {
                 error!("[document.rs -> editor.rs]: {error_msg} - could not save file.");
             },
         }
+
+        if self.document.save_history.len() > saves_before {
+            if let Some(record) = self.document.save_history.last() {
+                log_save(*record, &self.document.metadata.path);
+            }
+        }
+
+        // if the gutter changed size, everything was wrapped again,
+        // so find the line that was at the top of the view
+        if self.mode == Mode::View
+        && self.document.gutter_digits != gutter_before {
+            if let Some(top_index) = self.document.first_row_of_line(top_line_no) {
+                self.view_pos.y = top_index;
+            }
+        }
+
+        self.progress.add_today(self.document.count.words.saturating_sub(words_before));
+
+        if let Some(celebration) = self.goals.check(self.progress.today(), self.document.count.words) {
+            self.message = Message::from(celebration);
+            if self.config.goal_bell {
+                if let Err(error_msg) = self.terminal.bell() {
+                    error!("[terminal.rs -> editor.rs]: {error_msg} - could not ring bell.");
+                }
+            }
+        }
+    }
+
+    // ctrl+t starts a sprint, or ends the current one early
+    fn toggle_sprint(&mut self) {
+        if self.sprint.is_some() {
+            self.end_sprint();
+        } else {
+            let words = self.words_written();
+
+            self.sprint = Some(Sprint::start(
+                self.config.sprint_length,
+                words,
+                self.document.saves,
+            ));
+            self.message = Message::from(format!("{} minute sprint started. go!", self.config.sprint_length));
+        }
+    }
+
+    fn toggle_focus(&mut self) {
+        self.focus = !self.focus;
+        if self.focus {
+            self.message = Message::from("focus mode on.".to_string());
+        } else {
+            self.message = Message::from("focus mode off.".to_string());
+        }
+    }
+
+    // each mode only lists the keys it handles. pressing ctrl+h
+    // again while a page is still showing goes on to the next one.
+    fn show_help(&mut self) {
+        let pages: &[&str] = if self.mode == Mode::Edit {
+            &EDIT_HELP_PAGES
+        } else {
+            &VIEW_HELP_PAGES
+        };
+        let next_page = pages
+        .iter()
+        .position(|page| *page == self.message.text)
+        .filter(|_| self.message.time.elapsed() < Duration::new(5, 0))
+        .map_or(0, |page| page.saturating_add(1) % pages.len());
+
+        if let Some(page) = pages.get(next_page) {
+            self.message = Message::from((*page).to_string());
+        }
+    }
+
+    // ctrl+w shows spaces, tabs and line breaks, so there's no
+    // surprises in what gets saved
+    fn toggle_invisibles(&mut self) {
+        let top_line_no = self.document.line_no_at(self.view_pos.y);
+
+        self.document.invisibles = !self.document.invisibles;
+        // zero-width characters take up a column when they're
+        // shown, so everything gets wrapped again
+        self.document.wrap_file();
+        self.document.wrap_buffer();
+        if self.mode == Mode::View {
+            if let Some(top_index) = self.document.first_row_of_line(top_line_no) {
+                self.view_pos.y = top_index;
+            }
+        }
+        self.snap_view();
+
+        if self.document.invisibles {
+            self.message = Message::from("showing invisible characters.".to_string());
+        } else {
+            self.message = Message::from("hiding invisible characters.".to_string());
+        }
+    }
+
+    // where the text being worked on starts, as a display row and
+    // a byte offset into that row. a paragraph is everything up to
+    // a line break, and since the cursor is always at the end of
+    // the document, everything after this point is lit up.
+    fn focus_start(&self) -> (usize, usize) {
+        let last_index = self.document.display_len().saturating_sub(1);
+        let row_content = |index| {
+            let (file_drow, buf_drow) = self.document.get_display_row(index);
+
+            [file_drow, buf_drow]
+            .iter()
+            .flatten()
+            .map(|drow| drow.content.as_str())
+            .collect::<String>()
+        };
+        let current_line_no = self.document.line_no_at(last_index);
+        let mut first_index = last_index;
+
+        while first_index > 0 && self.document.line_no_at(first_index.saturating_sub(1)) == current_line_no {
+            first_index = first_index.saturating_sub(1);
+        }
+        if self.config.focus == Focus::Paragraph {
+            return (first_index, 0)
+        }
+
+        let line = (first_index..=last_index)
+        .map(row_content)
+        .collect::<String>();
+        let sentence_start = line
+        .split_sentence_bound_indices()
+        .last()
+        .map_or(0, |(index, _)| index);
+        let mut row_start: usize = 0;
+
+        for index in first_index..last_index {
+            let row_end = row_start.saturating_add(row_content(index).len());
+
+            if sentence_start < row_end {
+                return (index, sentence_start.saturating_sub(row_start))
+            }
+            row_start = row_end;
+        }
+        (last_index, sentence_start.saturating_sub(row_start))
+    }
+
+    // a sprint can be started or ended before what's in the
+    // buffer is saved, so its words are counted along with the
+    // file's. they're counted again here, since a save empties
+    // the buffer without resetting its count.
+    fn words_written(&mut self) -> usize {
+        self.document.append_buffer.count_words();
+        self.document.count.words.saturating_add(self.document.append_buffer.word_count)
+    }
+
+    fn open_calendar(&mut self) {
+        self.calendar = Some(Calendar::from(&self.progress));
+    }
+
+    fn end_sprint(&mut self) {
+        if let Some(sprint) = self.sprint.take() {
+            let result = sprint.finish(self.words_written(), self.document.saves);
+
+            log_sprint(&result, &self.document.metadata.name);
+            self.message = Message::from(result.message());
+        }
+    }
+
+    // write this session's numbers to the file's stats, then
+    // start counting from scratch for whatever comes next
+    fn record_session(&mut self) {
+        let session_prose = self.document.written_this_session();
+
+        self.typing.finish();
+        log_session(&SessionRecord {
+            started_at: self.document.opened_at.fixed_offset(),
+            ended_at: Local::now().fixed_offset(),
+            words: session_prose.words,
+            chars: session_prose.characters,
+            start_offset: self.document.start_offset,
+            keystrokes: self.typing.keystrokes,
+            backspaces: self.typing.backspaces,
+            words_per_minute: self.typing.words_per_minute(session_prose.words),
+            longest_pause: self.typing.longest_pause.as_secs(),
+            bursts: self.typing.bursts,
+            longest_burst: self.typing.longest_burst,
+        }, &self.document.metadata.path);
     }
 
     pub fn open(&mut self) {
@@ -736,6 +1336,9 @@ impl Editor {
         ).unwrap_or(None);
         
         if let Some(file_name) = input {
+            self.end_sprint();
+            self.record_session();
+            self.typing = TypingStats::default();
             self.document.append_newline();
 
             let file_info = if self.config.open_search {
@@ -743,13 +1346,11 @@ impl Editor {
             } else {
                 Metadata::get_file_info(&file_name, false)
             };
-            let mut document;
-
-            document = Document::open(file_info);
-            Document::wrap_file(&mut document);
-            Document::wrap_buffer(&mut document);
-
-            self.document = document;
+            self.document = Editor::load_document(file_info, &self.config);
+            self.search.clear();
+            self.sessions_back = None;
+            self.goals = Goals::from(&self.config, &self.document.content, &self.document.metadata.extension);
+            self.goals.init_reached(self.progress.today(), self.document.count.words);
             
             if self.config.start_edit {
                 self.mode = Mode::Edit;
@@ -766,6 +1367,297 @@ impl Editor {
         };
     }
 
+    // jumps to a line number, or a percentage of the way
+    // through the document if it ends in %
+    fn go_to(&mut self) {
+        let input = self.prompt(
+            "go to line (or 50%): ", 21, |_, _, _| {}
+        ).unwrap_or(None);
+        let Some(input) = input else {
+            return
+        };
+        let input = input.trim();
+        let last_index = self.document.display_len().saturating_sub(1);
+
+        let index = if let Some(percent) = input.strip_suffix('%') {
+            percent
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .map(|percent| last_index.saturating_mul(percent.min(100)) / 100)
+        } else {
+            input
+            .parse::<usize>()
+            .ok()
+            .map(|line_no| self.document
+                .first_row_of_line(line_no.max(1))
+                .unwrap_or(last_index)
+            )
+        };
+
+        if let Some(index) = index {
+            self.view_pos.y = index.min(self.max_view_y());
+        } else {
+            self.message = Message::from(format!("\"{input}\" isn't a line number or a percentage."));
+        }
+    }
+
+    // jumps to where this session started. pressing it again goes
+    // back to where each session before it started, newest first,
+    // then around to this one again.
+    fn jump_to_session(&mut self) {
+        let content_len = self.document.content.len();
+        let sessions = read_sessions(&self.document.metadata.path);
+        let mut starts = vec![(self.document.start_offset, None)];
+
+        // sessions where nothing was written start in the same
+        // place as the one after them, so they're skipped
+        for session in sessions.iter().rev() {
+            if session.start_offset <= content_len
+            && starts.last().is_none_or(|(offset, _)| *offset != session.start_offset) {
+                starts.push((session.start_offset, Some(session.started_at)));
+            }
+        }
+
+        let back = self.sessions_back
+        .map_or(0, |back| back.saturating_add(1))
+        % starts.len();
+        let Some((offset, started_at)) = starts.get(back).copied() else {
+            return
+        };
+        let index = self.document
+        .row_at_offset(offset)
+        .unwrap_or(self.document.display_len().saturating_sub(1));
+
+        self.sessions_back = Some(back);
+        self.view_pos.y = index.min(self.max_view_y());
+        self.message = Message::from(match started_at {
+            None => "this session started here. press ctrl+r again for earlier ones.".to_string(),
+            Some(started_at) => format!(
+                "a session from {} started here ({back} of {}).",
+                time_label(started_at.with_timezone(&Local)),
+                starts.len().saturating_sub(1),
+            ),
+        });
+    }
+
+    // bookmarks the line at the top of the screen. a line can
+    // only have one bookmark, so this renames any it had.
+    fn add_bookmark(&mut self) {
+        let line_no = self.document.line_no_at(self.view_pos.y);
+        let offset = self.document.line_start(line_no);
+
+        // bookmarks are kept by where they are in the file,
+        // and the buffer isn't in the file yet
+        if offset >= self.document.content.len() {
+            self.message = Message::from("save first to bookmark what you've just written.".to_string());
+            return
+        }
+        let input = self.prompt(
+            "bookmark name: ", 15, |_, _, _| {}
+        ).unwrap_or(None);
+        let Some(name) = input else {
+            self.message = Message::from("bookmark aborted".to_string());
+            return
+        };
+
+        let mut bookmarks = read_bookmarks(&self.document.metadata.path);
+        bookmarks.retain(|bookmark| bookmark.offset != offset);
+        bookmarks.push(Bookmark {
+            offset,
+            name: name.trim().to_string(),
+        });
+        bookmarks.sort_by_key(|bookmark| bookmark.offset);
+        write_bookmarks(&bookmarks, &self.document.metadata.path);
+        self.message = Message::from(format!("bookmarked line {line_no} as \"{}\".", name.trim()));
+    }
+
+    fn open_bookmarks(&mut self) {
+        let mut bookmarks = read_bookmarks(&self.document.metadata.path);
+        // bookmarks past the end of the file (if it was cut
+        // down somewhere else) are kept, but not shown
+        let items = bookmarks
+        .iter()
+        .filter_map(|bookmark| {
+            let (line_no, _) = self.document.line_at_offset(bookmark.offset)?;
+
+            Some(PickerItem {
+                label: bookmark.name.clone(),
+                detail: format!("line {line_no}"),
+                offset: bookmark.offset,
+            })
+        })
+        .collect();
+        let Some(picker) = self.pick(Picker::new("bookmarks", items, true)) else {
+            return
+        };
+
+        if !picker.removed.is_empty() {
+            bookmarks.retain(|bookmark| !picker.removed.contains(&bookmark.offset));
+            write_bookmarks(&bookmarks, &self.document.metadata.path);
+        }
+        if let Some(offset) = picker.chosen {
+            self.jump_to_offset(offset);
+        }
+    }
+
+    // lists the headings in a markdown file, with how many
+    // words are in each one's section
+    fn open_outline(&mut self) {
+        if self.document.metadata.extension != Extension::Md {
+            self.message = Message::from("only markdown files have an outline.".to_string());
+            return
+        }
+        let text = format!("{}{}", self.document.content, self.document.append_buffer.buffer);
+        let items = outline(&text)
+        .into_iter()
+        .map(|heading| PickerItem {
+            label: format!("{}{}", "  ".repeat(heading.level.saturating_sub(1)), heading.title),
+            detail: format!("{} words | line {}", heading.words, heading.line_no),
+            offset: heading.offset,
+        })
+        .collect();
+        let Some(picker) = self.pick(Picker::new("outline", items, false)) else {
+            return
+        };
+
+        if let Some(offset) = picker.chosen {
+            self.jump_to_offset(offset);
+        }
+    }
+
+    // shows a picker over the text, with a prompt to narrow it
+    // down, and hands it back once enter or esc is pressed
+    fn pick(&mut self, picker: Picker) -> Option<Picker> {
+        self.picker = Some(picker);
+        if let Err(error_msg) = self.refresh_screen() {
+            error!("[editor.rs]: {error_msg} - could not refresh screen to show picker.");
+        }
+
+        let result = self.prompt(
+            "filter: ", 8, |editor, key, filter| {
+                if key.kind == KeyEventKind::Release {
+                    return
+                }
+                if let Some(picker) = editor.picker.as_mut() {
+                    match key.code {
+                        KeyCode::Up => picker.select_previous(),
+                        KeyCode::Down => picker.select_next(),
+                        KeyCode::Delete => picker.remove(),
+                        KeyCode::Enter => picker.choose(),
+                        _ => picker.set_filter(filter),
+                    };
+                }
+                if let Err(error_msg) = editor.refresh_screen() {
+                    error!("[editor.rs]: {error_msg} - could not refresh screen while picking.");
+                }
+            }
+        );
+        if let Err(error_msg) = result {
+            error!("[editor.rs]: {error_msg} - could not read picker input.");
+        }
+
+        self.picker.take()
+    }
+
+    // puts the row with the byte at offset in the file at the
+    // top of the screen, unless that's past the end of the file
+    fn jump_to_offset(&mut self, offset: usize) {
+        if let Some(index) = self.document.row_at_offset(offset) {
+            self.view_pos.y = index.min(self.max_view_y());
+        }
+    }
+
+    // searches as you type, moving the view to the first match
+    // from where it was. esc puts the view back where it started.
+    fn search(&mut self) {
+        let start_y = self.view_pos.y;
+        let start_line_no = self.document.line_no_at(start_y);
+        let text = format!("{}{}", self.document.content, self.document.append_buffer.buffer);
+        self.search.active = true;
+
+        let input = self.prompt(
+            "search (alt+c case, alt+w word, alt+r regex): ", 46, |editor, key, query| {
+                if key.kind == KeyEventKind::Release {
+                    return
+                }
+                match (key.modifiers, key.code) {
+                    (KeyModifiers::ALT, KeyCode::Char('c')) => editor.search.ignore_case = !editor.search.ignore_case,
+                    (KeyModifiers::ALT, KeyCode::Char('w')) => editor.search.whole_word = !editor.search.whole_word,
+                    (KeyModifiers::ALT, KeyCode::Char('r')) => editor.search.regex = !editor.search.regex,
+                    _ => (),
+                };
+                editor.search.find(query, &text);
+                if let Some(found) = editor.search.first_from(start_line_no).cloned() {
+                    editor.jump_to_match(&found);
+                } else {
+                    editor.view_pos.y = start_y;
+                }
+                if let Err(error_msg) = editor.refresh_screen() {
+                    error!("[editor.rs]: {error_msg} - could not refresh screen while searching.");
+                }
+            }
+        ).unwrap_or(None);
+
+        if input.is_some() {
+            self.message = Message::from(self.match_message());
+        } else {
+            self.search.clear();
+            self.view_pos.y = start_y;
+            self.message = Message::from("search aborted".to_string());
+        }
+    }
+
+    fn next_match(&mut self, forward: bool) {
+        if let Some(found) = self.search.step(forward).cloned() {
+            self.jump_to_match(&found);
+        }
+        self.message = Message::from(self.match_message());
+    }
+
+    // puts the row with the match at the top of the screen,
+    // unless that's past the end of the file
+    fn jump_to_match(&mut self, found: &Match) {
+        if let Some(index) = self.document.row_of(found.line_no, found.range.start) {
+            self.view_pos.y = index.min(self.max_view_y());
+        }
+    }
+
+    fn match_message(&self) -> String {
+        let search = &self.search;
+
+        if search.query.is_empty() {
+            "nothing to search for. press ctrl+f to search.".to_string()
+        } else if search.invalid {
+            format!("\"{}\" isn't a valid regex.", search.query)
+        } else if search.matches.is_empty() {
+            format!("no matches for \"{}\".", search.query)
+        } else {
+            format!(
+                "match {} of {} | n - next | N - previous",
+                search.current.saturating_add(1),
+                search.matches.len(),
+            )
+        }
+    }
+
+    // the search matches on the display row at index, as byte
+    // ranges of the row's text
+    fn highlights(&self, index: usize, line_no: usize) -> Vec<Range<usize>> {
+        let mut on_line = self.search.on_line(line_no).peekable();
+
+        if on_line.peek().is_none() {
+            return Vec::new()
+        }
+        let row_start = self.document.offset_in_line(index);
+        let row_end = row_start.saturating_add(self.document.content_len_at(index));
+
+        on_line
+        .filter(|range| range.start < row_end && range.end > row_start)
+        .map(|range| range.start.max(row_start).saturating_sub(row_start)..range.end.min(row_end).saturating_sub(row_start))
+        .collect()
+    }
+
     // BAD: this whole closures and callbacks thing is a bit beyond me
     // so for now i'm just going to hope nothing breaks here
     // too bad!
@@ -788,31 +1680,33 @@ impl Editor {
             self.message = Message::from(format!("{prompt}{user_input}"));
             self.refresh_prompt()?;
             let event = read()?;
+            // the callback sees enter and esc too, so
+            // it knows how the prompt was closed
+            let mut done = false;
 
             // BAD: windows boilerplate
             if OS == "windows" {
                 if let Event::Key(key) = event {
                     match (key.kind, key.code) {
                         (KeyEventKind::Press, KeyCode::Backspace) => {
-                            user_input.truncate(user_input.len().saturating_sub(1));
-                            self.cursor_pos = Position {
-                                y: message_bar_y,
-                                x: Editor::prompt_cursor_x(start_x, self.cursor_pos.x, &Direction::Left),
-                            };
-                        },
-                        (KeyEventKind::Press, KeyCode::Enter) => break,
-                        (KeyEventKind::Press, KeyCode::Char(character)) => {
-                            if !character.is_control() {
-                                user_input.push(character);
+                            if let Some(removed) = user_input.pop() {
                                 self.cursor_pos = Position {
                                     y: message_bar_y,
-                                    x: Editor::prompt_cursor_x(start_x, self.cursor_pos.x, &Direction::Right),
+                                    x: Editor::prompt_cursor_x(start_x, self.cursor_pos.x, &Direction::Left, char_width(removed)),
                                 };
                             }
                         },
+                        (KeyEventKind::Press, KeyCode::Enter) => done = true,
+                        (KeyEventKind::Press, KeyCode::Char(character)) if !character.is_control() && !is_shortcut(key.modifiers) => {
+                            user_input.push(character);
+                            self.cursor_pos = Position {
+                                y: message_bar_y,
+                                x: Editor::prompt_cursor_x(start_x, self.cursor_pos.x, &Direction::Right, char_width(character)),
+                            };
+                        },
                         (KeyEventKind::Press, KeyCode::Esc) => {
                             user_input.truncate(0);
-                            break;
+                            done = true;
                         },
                         _ => (),
                     };
@@ -821,30 +1715,32 @@ impl Editor {
             } else if let Event::Key(key) = event {
                 match key.code {
                     KeyCode::Backspace => {
-                        user_input.truncate(user_input.len().saturating_sub(1));
-                        self.cursor_pos = Position {
-                            y: message_bar_y,
-                            x: Editor::prompt_cursor_x(start_x, self.cursor_pos.x, &Direction::Left),
-                        };
-                    },
-                    KeyCode::Enter => break,
-                    KeyCode::Char(character) => {
-                        if !character.is_control() {
-                            user_input.push(character);
+                        if let Some(removed) = user_input.pop() {
                             self.cursor_pos = Position {
                                 y: message_bar_y,
-                                x: Editor::prompt_cursor_x(start_x, self.cursor_pos.x, &Direction::Right),
+                                x: Editor::prompt_cursor_x(start_x, self.cursor_pos.x, &Direction::Left, char_width(removed)),
                             };
                         }
                     },
+                    KeyCode::Enter => done = true,
+                    KeyCode::Char(character) if !character.is_control() && !is_shortcut(key.modifiers) => {
+                        user_input.push(character);
+                        self.cursor_pos = Position {
+                            y: message_bar_y,
+                            x: Editor::prompt_cursor_x(start_x, self.cursor_pos.x, &Direction::Right, char_width(character)),
+                        };
+                    },
                     KeyCode::Esc => {
                         user_input.truncate(0);
-                        break;
+                        done = true;
                     },
                     _ => (),
                 };
                 callback(self, key, &user_input);
             }
+            if done {
+                break
+            }
         }
         self.message = Message::from(String::new());
         
@@ -854,14 +1750,16 @@ impl Editor {
         Ok(Some(user_input))
     }
 
-    fn prompt_cursor_x(start_x: usize, at_x: usize, direction: &Direction) -> usize {
+    // width is how many columns the character being added
+    // or removed takes up
+    fn prompt_cursor_x(start_x: usize, at_x: usize, direction: &Direction, width: usize) -> usize {
         match direction {
-            Direction::Left => if at_x.saturating_sub(1) < start_x {
+            Direction::Left => if at_x.saturating_sub(width) < start_x {
                 return start_x;
             } else {
-                return at_x.saturating_sub(1);
+                return at_x.saturating_sub(width);
             },
-            Direction::Right => return at_x.saturating_add(1),
+            Direction::Right => return at_x.saturating_add(width),
             _ => (),
         };
         0
@@ -881,3 +1779,211 @@ impl Editor {
         Ok(())
     }
 }
+
+// keys held with just ctrl or just alt are shortcuts, so they aren't
+// typed into prompts. altgr shows up as both on some keyboards, and
+// that's needed to type things like @, so it still counts as typing.
+fn is_shortcut(modifiers: KeyModifiers) -> bool {
+    modifiers == KeyModifiers::CONTROL || modifiers == KeyModifiers::ALT
+}
+
+fn char_width(character: char) -> usize {
+    grapheme_width(character.encode_utf8(&mut [0; 4]))
+}
+
+// how many bytes of the row at index focus mode dims
+fn dimmed_len(focus_start: Option<(usize, usize)>, index: usize) -> usize {
+    match focus_start {
+        Some((start_index, _)) if index < start_index => usize::MAX,
+        Some((start_index, start_byte)) if index == start_index => start_byte,
+        _ => 0,
+    }
+}
+
+// where a row's trailing spaces start, as a byte offset into its
+// content. rows that don't end a line don't have any.
+fn trailing_from(content: &str, line_ends: bool) -> usize {
+    if line_ends {
+        content.trim_end_matches(' ').len()
+    } else {
+        content.len()
+    }
+}
+
+// renders the piece of a row's content from byte start. any of
+// it past trailing is trailing spaces, which get shown if the
+// invisible characters are.
+fn render_piece(piece: &str, start: usize, column: usize, rendering: Rendering, trailing: usize) -> String {
+    let mut rendered = render(piece, column, rendering);
+    let trailing_len = start
+    .saturating_add(piece.len())
+    .saturating_sub(trailing.max(start));
+
+    if rendering.invisibles && trailing_len > 0 {
+        rendered.truncate(rendered.len().saturating_sub(trailing_len));
+        rendered.push_str(&SPACE_GLYPH.repeat(trailing_len));
+    }
+    rendered
+}
+
+// prints some of a row, with the first dimmed bytes dimmed.
+// column is where on the row it starts, for lining up tabs.
+fn print_focused(terminal: &mut Terminal, content: &str, dimmed: usize, part: Part, column: usize, rendering: Rendering, trailing: usize) -> Result<(), Error> {
+    let split_at = dimmed.min(content.len());
+    let (dim_part, lit_part) = match (content.get(..split_at), content.get(split_at..)) {
+        (Some(dim_part), Some(lit_part)) => (dim_part, lit_part),
+        _ => ("", content),
+    };
+
+    if !dim_part.is_empty() {
+        terminal.queue_print_dimmed(&render_piece(dim_part, 0, column, rendering, trailing), part)?;
+    }
+    if !lit_part.is_empty() {
+        let lit_column = column.saturating_add(width_at(dim_part, column, rendering));
+        terminal.queue_print_as(&render_piece(lit_part, dim_part.len(), lit_column, rendering, trailing), part)?;
+    }
+    Ok(())
+}
+
+// prints a row's content, with its markdown styling if it has any
+fn print_content(terminal: &mut Terminal, drow: &DisplayRow, column: usize, styled: bool, dim_markers: bool, rendering: Rendering, trailing: usize) -> Result<(), Error> {
+    if !styled || drow.spans.is_empty() {
+        return terminal.queue_print(&render_piece(&drow.content, 0, column, rendering, trailing))
+    }
+    let mut span_column = column;
+
+    for span in &drow.spans {
+        let Some(text) = drow.content.get(span.start..span.end) else {
+            continue
+        };
+        if span.style.quote {
+            terminal.queue_print_styled(QUOTE_BAR, span.style, dim_markers)?;
+        } else {
+            terminal.queue_print_styled(&render_piece(text, span.start, span_column, rendering, trailing), span.style, dim_markers)?;
+        }
+        span_column = span_column.saturating_add(width_at(text, span_column, rendering));
+    }
+    Ok(())
+}
+
+// a piece of a row that's all drawn the same way, as a byte
+// range of the row
+struct Piece {
+    range: Range<usize>,
+    part: Part,
+    dimmed: bool,
+    trailing: bool,
+    // true if the piece is part of a search match
+    highlighted: bool,
+    style: Option<Style>,
+}
+
+// puts the file's and the buffer's parts of a row together, and
+// cuts it up wherever the way it's drawn changes. highlights are
+// the search matches on the row.
+fn row_pieces(file_drow: Option<&DisplayRow>, buf_drow: Option<&DisplayRow>, styled: bool, dimmed: usize, line_ends: bool, buffer_part: Part, highlights: &[Range<usize>]) -> (String, Vec<Piece>) {
+    let file_content = file_drow.map_or("", |drow| drow.content.as_str());
+    let file_len = file_content.len();
+    let row = format!("{file_content}{}", buf_drow.map_or("", |drow| drow.content.as_str()));
+    let trailing = trailing_from(&row, line_ends);
+    let spans = if styled {
+        file_drow
+        .iter()
+        .flat_map(|drow| drow.spans.iter().copied())
+        .chain(buf_drow.iter().flat_map(|drow| drow.spans.iter().map(|span| Span {
+            start: span.start.saturating_add(file_len),
+            end: span.end.saturating_add(file_len),
+            style: span.style,
+        })))
+        .collect()
+    } else {
+        Vec::new()
+    };
+
+    let mut edges = vec![0, file_len, row.len(), dimmed.min(row.len()), trailing];
+    edges.extend(spans.iter().flat_map(|span| [span.start, span.end]));
+    edges.extend(highlights.iter().flat_map(|range| [range.start, range.end]));
+    edges.sort_unstable();
+    edges.dedup();
+
+    let pieces = edges
+    .windows(2)
+    .filter_map(|edge| match edge {
+        [start, end] if start < end => Some(Piece {
+            range: *start..*end,
+            part: if *start < file_len {
+                Part::Text
+            } else {
+                buffer_part
+            },
+            dimmed: *start < dimmed,
+            trailing: *start >= trailing,
+            highlighted: highlights.iter().any(|range| range.contains(start)),
+            style: spans
+            .iter()
+            .find(|span| span.start <= *start && *start < span.end)
+            .map(|span| span.style),
+        }),
+        _ => None,
+    })
+    .collect();
+
+    (row, pieces)
+}
+
+// prints a row one bidi run at a time. right-to-left runs are
+// printed backwards, pieces and all.
+fn print_pieces(terminal: &mut Terminal, row: &str, pieces: &[Piece], rtl: bool, column: usize, rendering: Rendering, dim_markers: bool) -> Result<(), Error> {
+    let mut column = column;
+
+    for (run, run_rtl) in visual_runs(row, rtl) {
+        let mut run_pieces = pieces
+        .iter()
+        .filter(|piece| piece.range.start < run.end && piece.range.end > run.start)
+        .map(|piece| (piece.range.start.max(run.start)..piece.range.end.min(run.end), piece))
+        .collect::<Vec<_>>();
+        if run_rtl {
+            run_pieces.reverse();
+        }
+
+        for (range, piece) in run_pieces {
+            let text = if run_rtl {
+                reverse(row.get(range).unwrap_or_default())
+            } else {
+                row.get(range).unwrap_or_default().to_string()
+            };
+            let shown = if piece.trailing && rendering.invisibles {
+                SPACE_GLYPH.repeat(text.len())
+            } else if piece.style.is_some_and(|style| style.quote) {
+                QUOTE_BAR.to_string()
+            } else {
+                render(&text, column, rendering)
+            };
+
+            if piece.highlighted {
+                terminal.queue_print_as(&shown, Part::Match)?;
+            } else if let Some(style) = piece.style {
+                terminal.queue_print_styled(&shown, style, dim_markers)?;
+            } else if piece.dimmed {
+                terminal.queue_print_dimmed(&shown, piece.part)?;
+            } else {
+                terminal.queue_print_as(&shown, piece.part)?;
+            }
+            column = column.saturating_add(width_at(&text, column, rendering));
+        }
+    }
+    Ok(())
+}
+
+// finishes off a row that ends partway through a word, or
+// shows the line break at the end of a line
+fn print_row_end(terminal: &mut Terminal, drow: &DisplayRow, break_marker: bool, line_break: bool) -> Result<(), Error> {
+    if drow.hyphenated {
+        terminal.queue_print(HYPHEN)?;
+    } else if break_marker && drow.broken {
+        terminal.queue_print(BREAK_MARKER)?;
+    } else if line_break {
+        terminal.queue_print_dimmed(LINE_END_GLYPH, Part::Text)?;
+    }
+    Ok(())
+}
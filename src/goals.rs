@@ -0,0 +1,137 @@
+use crate::{Config, Extension};
+
+// -----------------
+
+const METER_WIDTH: usize = 10;
+
+// a goal of 0 means that goal is turned off. the reached
+// flags make sure we only celebrate once per session.
+pub struct Goals {
+    pub daily_goal: usize,
+    pub document_goal: usize,
+    daily_reached: bool,
+    document_reached: bool,
+}
+
+impl Goals {
+    // goals in the config file can be overridden per file
+    // by front matter at the very top of a markdown file:
+    // ---
+    // daily-goal: 500
+    // document-goal: 50000
+    // ---
+    pub fn from(config: &Config, content: &str, extension: &Extension) -> Self {
+        let mut daily_goal = config.daily_goal;
+        let mut document_goal = config.document_goal;
+
+        if *extension == Extension::Md {
+            let mut lines = content.lines();
+
+            if lines.next().is_some_and(|line| line.trim_end() == "---") {
+                for line in lines.take_while(|line| line.trim_end() != "---") {
+                    if let Some((key, value)) = line.split_once(':') {
+                        match (key.trim(), value.trim().parse::<usize>()) {
+                            ("daily-goal", Ok(goal)) => daily_goal = goal,
+                            ("document-goal", Ok(goal)) => document_goal = goal,
+                            _ => (),
+                        }
+                    }
+                }
+            }
+        }
+
+        Self {
+            daily_goal,
+            document_goal,
+            daily_reached: false,
+            document_reached: false,
+        }
+    }
+
+    // goals that are already met when the file is opened
+    // shouldn't be celebrated again on the first save
+    pub fn init_reached(&mut self, today: usize, total: usize) {
+        self.daily_reached = self.daily_goal > 0 && today >= self.daily_goal;
+        self.document_reached = self.document_goal > 0 && total >= self.document_goal;
+    }
+
+    // returns a message if a goal has just been reached
+    pub fn check(&mut self, today: usize, total: usize) -> Option<String> {
+        let mut reached = Vec::new();
+
+        if self.daily_goal > 0
+        && !self.daily_reached
+        && today >= self.daily_goal {
+            self.daily_reached = true;
+            reached.push(format!("daily goal of {} words", self.daily_goal));
+        }
+        if self.document_goal > 0
+        && !self.document_reached
+        && total >= self.document_goal {
+            self.document_reached = true;
+            reached.push(format!("document goal of {} words", self.document_goal));
+        }
+
+        if reached.is_empty() {
+            None
+        } else {
+            Some(format!("*** congratulations! you reached your {}! ***", reached.join(" and ")))
+        }
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.daily_goal > 0 || self.document_goal > 0
+    }
+
+    // e.g. "day [####......] 412/1000"
+    pub fn indicator(&self, today: usize, total: usize) -> String {
+        let mut indicators = Vec::new();
+
+        if self.daily_goal > 0 {
+            indicators.push(format!("day {} {today}/{} ", meter(today, self.daily_goal), self.daily_goal));
+        }
+        if self.document_goal > 0 {
+            indicators.push(format!("doc {} {total}/{} ", meter(total, self.document_goal), self.document_goal));
+        }
+
+        indicators.concat()
+    }
+
+    pub fn summary(&self, today: usize, total: usize) -> String {
+        let mut summary = Vec::new();
+
+        if self.is_set() {
+            summary.push("goal progress:\r\n".to_string());
+        }
+        if self.daily_goal > 0 {
+            summary.push(format!(
+                "  today: {today}/{} words ({}%)\r\n",
+                self.daily_goal,
+                percent(today, self.daily_goal),
+            ));
+        }
+        if self.document_goal > 0 {
+            summary.push(format!(
+                "  document: {total}/{} words ({}%)\r\n",
+                self.document_goal,
+                percent(total, self.document_goal),
+            ));
+        }
+
+        summary.concat()
+    }
+}
+
+fn percent(done: usize, goal: usize) -> usize {
+    done.saturating_mul(100).checked_div(goal).unwrap_or(0)
+}
+
+fn meter(done: usize, goal: usize) -> String {
+    let filled = done
+    .saturating_mul(METER_WIDTH)
+    .checked_div(goal)
+    .unwrap_or(0)
+    .min(METER_WIDTH);
+
+    format!("[{}{}]", "#".repeat(filled), ".".repeat(METER_WIDTH.saturating_sub(filled)))
+}
@@ -0,0 +1,109 @@
+use crate::SaveRecord;
+use chrono::{DateTime, Local};
+
+// -----------------
+
+// how wide a time in the gutter is, see time_label()
+pub const TIME_WIDTH: usize = 5;
+
+// what the gutter shows next to each line
+#[derive(PartialEq, Clone, Copy)]
+pub enum Gutter {
+    // the line's number in the file
+    Absolute,
+    // how far the line is from the one being viewed
+    Relative,
+    // which paragraph the line is, not counting blank lines
+    Paragraph,
+    // how many words come before the line
+    Words,
+    // when the line was saved to the file
+    Time,
+}
+
+impl Gutter {
+    pub fn from_name(name: &str) -> Option<Self> {
+        let gutter = match name {
+            "absolute" => Gutter::Absolute,
+            "relative" => Gutter::Relative,
+            "paragraph" => Gutter::Paragraph,
+            "words" => Gutter::Words,
+            "time" => Gutter::Time,
+            _ => return None,
+        };
+
+        Some(gutter)
+    }
+
+    // line numbers are already in the display rows, but
+    // everything else needs the lines counted up
+    pub fn needs_stats(self) -> bool {
+        matches!(self, Gutter::Paragraph | Gutter::Words | Gutter::Time)
+    }
+}
+
+// what the gutter needs to know about a line of the file
+#[derive(Clone, Copy, Default)]
+pub struct LineStats {
+    // where the line starts in the file, in bytes
+    pub offset: usize,
+    pub words_before: usize,
+    pub words: usize,
+    // how many paragraphs there are up to and including this line
+    pub paragraph: usize,
+    pub blank: bool,
+}
+
+// counts up the lines in text, which starts at offset in the file.
+// previous is the line just before text, if there is one, so the
+// counts can carry on from where it left off.
+pub fn line_stats(text: &str, offset: usize, previous: Option<&LineStats>) -> Vec<LineStats> {
+    let mut stats = Vec::new();
+    let mut last = previous.copied();
+    let mut line_offset = offset;
+
+    for line in text.split('\n') {
+        let blank = line.trim().is_empty();
+        let words_before = last.map_or(0, |last| last.words_before.saturating_add(last.words));
+        let paragraphs_before = last.map_or(0, |last| last.paragraph);
+        let line_stats = LineStats {
+            offset: line_offset,
+            words_before,
+            words: words_count::count(line).words,
+            paragraph: if blank {
+                paragraphs_before
+            } else {
+                paragraphs_before.saturating_add(1)
+            },
+            blank,
+        };
+
+        stats.push(line_stats);
+        last = Some(line_stats);
+        line_offset = line_offset.saturating_add(line.len()).saturating_add(1);
+    }
+
+    stats
+}
+
+// the saves are in the order they happened, and brr only ever
+// adds to the end of the file, so the first save that went past
+// the offset is the one that wrote it. text that was there before
+// brr started keeping track doesn't have a time.
+pub fn committed_at(saves: &[SaveRecord], offset: usize) -> Option<DateTime<Local>> {
+    let index = saves.partition_point(|save| save.end <= offset);
+
+    saves
+    .get(index)
+    .filter(|save| save.start <= offset)
+    .map(|save| save.saved_at.with_timezone(&Local))
+}
+
+// lines saved today show the time, and older lines the date
+pub fn time_label(time: DateTime<Local>) -> String {
+    if time.date_naive() == Local::now().date_naive() {
+        time.format("%H:%M").to_string()
+    } else {
+        time.format("%m-%d").to_string()
+    }
+}
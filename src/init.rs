@@ -1,5 +1,8 @@
-use crate::{Editor, Metadata, Config};
-use std::io::{Error, Write, stdin, stdout};
+use crate::{Editor, Metadata, Config, Calendar, report};
+use std::{
+    io::{Error, Write, stdin, stdout},
+    path::PathBuf,
+};
 
 // -----------------
 
@@ -20,19 +23,27 @@ const HELP: &str = "brr help:\r
   -> usage: brr [OPTIONS/COMMANDS] [FILENAME]\r
   \r
   -h / help    - print help\r
-  -v / version - print version";
+  -v / version - print version\r
+  calendar     - show your writing calendar\r
+  stats [FILE/FOLDER] [--json]\r
+               - print word counts, sessions, words per\r
+                 day and longest streak for a file, or\r
+                 every .txt and .md file in a folder";
 
 #[derive(Default)]
 pub struct Init {
     user_input: Option<String>,
+    // any arguments after the first one
+    args: Vec<String>,
     config: Config,
 }
 
 impl Init {
-    pub fn welcome(mut self, initial_input: Option<String>) -> Result<(), Error>{
+    pub fn welcome(mut self, initial_input: Option<String>, args: Vec<String>) -> Result<(), Error>{
         // set configs
         self.config = Config::get_config();
         self.user_input = initial_input;
+        self.args = args;
         // this is so that we can just quit if options are
         // called and we're not within the dialogue loop.
         let mut exit_after = true;
@@ -60,6 +71,29 @@ impl Init {
                         stdout().flush()?;
                         self.get_user_input();
                     },
+                    "calendar" => {
+                        Calendar::load().show()?;
+                        if exit_after {
+                            break
+                        };
+                        print!("{PROMPT}");
+                        stdout().flush()?;
+                        self.get_user_input();
+                    },
+                    stats if stats == "stats"
+                    || stats.starts_with("stats ") => {
+                        let printed = self.print_stats();
+                        if exit_after {
+                            // so scripts can tell it didn't work
+                            if !printed {
+                                std::process::exit(1)
+                            };
+                            break
+                        };
+                        print!("{PROMPT}");
+                        stdout().flush()?;
+                        self.get_user_input();
+                    },
                     _ => {
                         let to_open = if self.config.open_search {
                             Metadata::get_file_info(input, true)
@@ -81,6 +115,34 @@ impl Init {
         Ok(())
     }
 
+    // arguments can come from the command line (brr stats notes.txt)
+    // or from the welcome prompt (> stats notes.txt). returns
+    // false if the stats couldn't be read.
+    fn print_stats(&mut self) -> bool {
+        let mut args = std::mem::take(&mut self.args);
+
+        if let Some(input) = self.user_input.as_deref().and_then(|input| input.strip_prefix("stats")) {
+            args.extend(prompt_args(input));
+        }
+
+        let json = args.iter().any(|arg| arg == "--json");
+        let target = args
+        .into_iter()
+        .find(|arg| arg != "--json")
+        .map_or(PathBuf::from("."), PathBuf::from);
+
+        match report(&target, json) {
+            Ok(stats) => {
+                println!("{stats}");
+                true
+            },
+            Err(error_msg) => {
+                eprintln!("{error_msg}");
+                false
+            },
+        }
+    }
+
     fn get_user_input(&mut self) {
         let mut input = String::new();
 
@@ -91,3 +153,27 @@ impl Init {
         self.user_input = Some(input.trim().to_string());
     }
 }
+
+// the command line's arguments come through as they are, but the
+// prompt's come in as one line. splitting that on spaces would
+// split up file names with spaces in them, so everything but
+// --json is taken as the path, quotes around it or not.
+fn prompt_args(input: &str) -> Vec<String> {
+    let mut path = input.trim();
+    let mut args = Vec::new();
+
+    if let Some(before) = path.strip_suffix("--json") {
+        args.push(String::from("--json"));
+        path = before.trim_end();
+    } else if let Some(after) = path.strip_prefix("--json") {
+        args.push(String::from("--json"));
+        path = after.trim_start();
+    }
+    let path = path.trim_matches(|character| character == '"' || character == '\'');
+
+    if !path.is_empty() {
+        args.push(path.to_string());
+    }
+
+    args
+}
@@ -11,6 +11,9 @@
     clippy::indexing_slicing,
     clippy::string_slice,
 )]
+// newer versions of clippy don't like the semicolons after
+// blocks, but i find them easier to read, so they're staying.
+#![allow(clippy::unnecessary_semicolon)]
 
 mod die;
 mod config;
@@ -21,16 +24,44 @@ mod metadata;
 mod document;
 mod append_buffer;
 mod row;
+mod stats;
+mod goals;
+mod sprint;
+mod typing;
+mod report;
+mod calendar;
+mod style;
+mod markdown;
+mod theme;
+mod gutter;
+mod bidi;
+mod search;
+mod bookmarks;
+mod picker;
 
 use die::die;
 use config::Config;
 use init::Init;
 use terminal::Terminal;
-use editor::{Editor, Position, SaveType};
-use metadata::{Metadata, get_conf_or_log_path};
-use document::{Document, render};
+use editor::{Editor, Position, SaveType, Focus};
+use metadata::{Metadata, Extension, get_conf_or_log_path, get_state_dir};
+use document::{Document, Rendering, SPACE_GLYPH, LINE_END_GLYPH, render, width_at, display_width, grapheme_width, truncate_to_width};
 use append_buffer::AppendBuffer;
 use row::DisplayRow;
+use stats::{Progress, SessionRecord, SaveRecord, log_save, read_saves, log_sprint, log_session, read_sessions, document_stats_path, longest_streak, current_streak};
+use goals::Goals;
+use sprint::{Sprint, SprintResult};
+use typing::TypingStats;
+use report::report;
+use calendar::Calendar;
+use style::{Style, Span};
+use markdown::{line_spans, style_rows, outline};
+use theme::{Theme, Part, THEME_KEYS, no_color};
+use bidi::{has_rtl, is_rtl_line, visual_runs, visual_column, reverse};
+use gutter::{Gutter, LineStats, TIME_WIDTH, line_stats, committed_at, time_label};
+use search::{Search, Match};
+use bookmarks::{Bookmark, read_bookmarks, write_bookmarks};
+use picker::{Picker, PickerItem};
 
 use {
     log::{LevelFilter, error, warn, info},
@@ -46,21 +77,13 @@ use {
 // the append buffer
 
 // RE: mouse events
-// as far as i can tell, there's no easy way to capture mouse
-// events without causing a whole bunch of unneccessary loops
-// of brr's editor.rs->run() function, which i just don't like.
-// it causes the cursor to flicker when the mouse is moved
-// and i find that distracting and ugly. because it's not a
-// feature that feels very important to brr's utility, i won't
-// be implementing it at this time.
-// IDEA: maybe i could try my hand at asyncronous handling of
-// mouse events, so that they run separate from the program's
-// main loop and only actually affect something when they would
-// be needed to?
-
-// BUG: singular words that are longer than the entire width of the terminal break
-// the line wrapping and display. i'm not going to fix this right now because it's
-// a fairly unreachable edge case, but 
+// capturing the mouse means every little movement comes through
+// editor.rs->run() as an event, and redrawing for each one made
+// the cursor flicker. so the mouse is off unless it's turned on
+// in the config, and even then it's only captured in view mode
+// (where the cursor is hidden anyway). movement on its own is
+// skipped without a redraw, and bursts of wheel or drag events
+// are all handled before the screen is redrawn once.
 
 // TODO:
 //   - !!! clean up save type detection in editor.rs + document.rs
@@ -75,15 +98,13 @@ use {
 // to open the log file.
 // MAYBE:
 //   -     don't wrap spaces along with words
-//   -     add search function to viewing mode
-//   -     scrollbar
 //   -     line numbers
-//   -     handle wide characters https://github.com/rhysd/kiro-editor
 //   -     truncate absolute paths?
 
 #[allow(clippy::unwrap_used)]
 fn main() {
-    let args = std::env::args().nth(1);
+    let mut args = std::env::args().skip(1);
+    let first_arg = args.next();
 
     if let Some(log_path) = get_conf_or_log_path(false) {
         log_to_file(&log_path, LevelFilter::Info).unwrap();
@@ -93,7 +114,7 @@ fn main() {
         panic!("cannot find executable. do you have permission to access the folder containing brr?")
     };
 
-    match Init::default().welcome(args) {
+    match Init::default().welcome(first_arg, args.collect()) {
         Ok(()) => (),
         Err(error_msg) => error!("[init.rs -> main.rs]: {error_msg} - couldn't flush stdout."),
     };
@@ -0,0 +1,278 @@
+use crate::{Style, Span, DisplayRow};
+
+// -----------------
+
+// checked in this order, so ** is found before *
+const DELIMITERS: [&str; 5] = ["`", "**", "__", "*", "_"];
+
+// splits a line of markdown into styled spans that cover the
+// whole line. this only knows about the things brr renders
+// (headings, block quotes, emphasis, strong text and inline
+// code), and it only looks at one line at a time, so anything
+// spread over several lines is left alone.
+pub fn line_spans(line: &str) -> Vec<Span> {
+    let mut spans = Vec::new();
+
+    if let Some(heading_level) = heading_level(line) {
+        let after_hashes = line.get(heading_level..).unwrap_or_default();
+        let heading = Style {
+            bold: true,
+            ..Default::default()
+        };
+        let marker_end = heading_level.saturating_add(usize::from(!after_hashes.is_empty()));
+
+        spans.push(Span {
+            start: 0,
+            end: marker_end,
+            style: Style {
+                marker: true,
+                ..heading
+            },
+        });
+        inline_spans(line, marker_end, heading, &mut spans);
+        return spans
+    }
+
+    // block quotes can be nested, so draw a bar for each >
+    let mut quote_end = 0;
+    while line.get(quote_end..).is_some_and(|rest| rest.starts_with('>')) {
+        spans.push(Span {
+            start: quote_end,
+            end: quote_end.saturating_add(1),
+            style: Style {
+                quote: true,
+                marker: true,
+                ..Default::default()
+            },
+        });
+        quote_end = quote_end.saturating_add(1);
+        if line.get(quote_end..).is_some_and(|rest| rest.starts_with(' ')) {
+            quote_end = quote_end.saturating_add(1);
+            spans.push(Span {
+                start: quote_end.saturating_sub(1),
+                end: quote_end,
+                style: Style::default(),
+            });
+        }
+    }
+    inline_spans(line, quote_end, Style::default(), &mut spans);
+
+    spans
+}
+
+// how deep a heading is (how many #s it starts with),
+// or none if the line isn't a heading
+pub fn heading_level(line: &str) -> Option<usize> {
+    let level = line.chars().take_while(|character| *character == '#').count();
+    let after_hashes = line.get(level..).unwrap_or_default();
+
+    if (1..=6).contains(&level)
+    && (after_hashes.is_empty() || after_hashes.starts_with(' ')) {
+        Some(level)
+    } else {
+        None
+    }
+}
+
+// a heading in a markdown file. its section is everything up to
+// the next heading at the same level or above, so a chapter's
+// words include the words in its scenes.
+pub struct Heading {
+    pub level: usize,
+    pub title: String,
+    pub line_no: usize,
+    // where the heading starts in the text, in bytes
+    pub offset: usize,
+    // how many words are in the section, not counting headings
+    pub words: usize,
+}
+
+// finds every heading in text. lines in fenced code blocks
+// can start with # (like comments) without being headings.
+pub fn outline(text: &str) -> Vec<Heading> {
+    let mut headings: Vec<Heading> = Vec::new();
+    // the headings whose sections the current line is in
+    let mut open: Vec<usize> = Vec::new();
+    let mut in_fence = false;
+    let mut offset: usize = 0;
+
+    for (index, line) in text.split('\n').enumerate() {
+        let line_offset = offset;
+        offset = offset.saturating_add(line.len()).saturating_add(1);
+
+        if line.trim_start().starts_with("```") || line.trim_start().starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        if let Some(level) = heading_level(line).filter(|_| !in_fence) {
+            while open
+            .last()
+            .and_then(|open_index| headings.get(*open_index))
+            .is_some_and(|heading| heading.level >= level) {
+                open.pop();
+            }
+            open.push(headings.len());
+            headings.push(Heading {
+                level,
+                title: heading_title(line, level),
+                line_no: index.saturating_add(1),
+                offset: line_offset,
+                words: 0,
+            });
+        } else {
+            let words = words_count::count(line).words;
+
+            for open_index in &open {
+                if let Some(heading) = headings.get_mut(*open_index) {
+                    heading.words = heading.words.saturating_add(words);
+                }
+            }
+        }
+    }
+
+    headings
+}
+
+// a heading's text without its #s. closing #s only count
+// if there's a space before them, so "C#" keeps its #.
+fn heading_title(line: &str, level: usize) -> String {
+    let title = line.get(level..).unwrap_or_default().trim();
+    let without_closing = title.trim_end_matches('#');
+
+    if without_closing.is_empty() || without_closing.ends_with(' ') {
+        without_closing.trim_end().to_string()
+    } else {
+        title.to_string()
+    }
+}
+
+// goes through the line from start looking for emphasis, strong
+// text and code. a delimiter only opens if there's a matching one
+// later on to close it, so a lone * stays as it is.
+fn inline_spans(line: &str, start: usize, base: Style, spans: &mut Vec<Span>) {
+    let mut strong = false;
+    let mut emphasis = false;
+    let mut code = false;
+    let mut run_start = start;
+    let mut index = start;
+
+    while let Some(rest) = line.get(index..).filter(|rest| !rest.is_empty()) {
+        // skip over escaped characters like \*
+        if rest.starts_with('\\') {
+            let escaped_len = rest.chars().take(2).map(char::len_utf8).sum::<usize>();
+            index = index.saturating_add(escaped_len);
+            continue
+        }
+
+        let delimiter = DELIMITERS
+        .iter()
+        .find(|delimiter| rest.starts_with(**delimiter))
+        .filter(|delimiter| !code || **delimiter == "`");
+
+        let Some(delimiter) = delimiter else {
+            index = index.saturating_add(rest.chars().next().map_or(1, char::len_utf8));
+            continue
+        };
+        let is_open = match *delimiter {
+            "`" => code,
+            "**" | "__" => strong,
+            _ => emphasis,
+        };
+        let delimiter_end = index.saturating_add(delimiter.len());
+
+        if !can_toggle(line, index, delimiter, is_open) {
+            index = delimiter_end;
+            continue
+        }
+
+        push_span(spans, run_start, index, current_style(base, strong, emphasis, code));
+        // opening markers take on the style they start, and
+        // closing ones the style they end
+        if !is_open {
+            toggle(delimiter, &mut strong, &mut emphasis, &mut code);
+        }
+        push_span(spans, index, delimiter_end, Style {
+            marker: true,
+            ..current_style(base, strong, emphasis, code)
+        });
+        if is_open {
+            toggle(delimiter, &mut strong, &mut emphasis, &mut code);
+        }
+        index = delimiter_end;
+        run_start = index;
+    }
+    push_span(spans, run_start, line.len(), current_style(base, strong, emphasis, code));
+}
+
+fn can_toggle(line: &str, index: usize, delimiter: &str, is_open: bool) -> bool {
+    let before = line.get(..index).and_then(|before| before.chars().last());
+    let after_index = index.saturating_add(delimiter.len());
+    let after = line.get(after_index..).and_then(|after| after.chars().next());
+
+    // underscores in the middle of a word (like snake_case)
+    // aren't emphasis
+    if delimiter.starts_with('_') {
+        let next_to_word = if is_open { after } else { before };
+        if next_to_word.is_some_and(char::is_alphanumeric) {
+            return false
+        }
+    }
+
+    // closing markers need to come right after the text, and
+    // opening ones right before it
+    if delimiter == "`" {
+        is_open || line.get(after_index..).is_some_and(|rest| rest.contains('`'))
+    } else if is_open {
+        before.is_some_and(|before| !before.is_whitespace())
+    } else {
+        after.is_some_and(|after| !after.is_whitespace())
+        && line.get(after_index..).is_some_and(|rest| rest.contains(delimiter))
+    }
+}
+
+fn toggle(delimiter: &str, strong: &mut bool, emphasis: &mut bool, code: &mut bool) {
+    match delimiter {
+        "`" => *code = !*code,
+        "**" | "__" => *strong = !*strong,
+        _ => *emphasis = !*emphasis,
+    }
+}
+
+fn current_style(base: Style, strong: bool, emphasis: bool, code: bool) -> Style {
+    Style {
+        bold: base.bold || strong,
+        italic: base.italic || emphasis,
+        code,
+        ..base
+    }
+}
+
+fn push_span(spans: &mut Vec<Span>, start: usize, end: usize, style: Style) {
+    if start < end {
+        spans.push(Span {
+            start,
+            end,
+            style,
+        });
+    }
+}
+
+// hands out a line's spans to the display rows it was wrapped
+// into, moving them so they start from each row's content
+pub fn style_rows(rows: &mut [DisplayRow], spans: &[Span]) {
+    let mut row_start: usize = 0;
+
+    for row in rows {
+        let row_end = row_start.saturating_add(row.content.len());
+
+        row.spans = spans
+        .iter()
+        .filter(|span| span.start < row_end && span.end > row_start)
+        .map(|span| Span {
+            start: span.start.max(row_start).saturating_sub(row_start),
+            end: span.end.min(row_end).saturating_sub(row_start),
+            style: span.style,
+        })
+        .collect();
+        row_start = row_end;
+    }
+}
@@ -3,6 +3,7 @@ use {
         path::{Path, PathBuf},
         fs::{read_dir, create_dir_all},
         env::{current_dir, current_exe, var, consts::OS},
+        sync::OnceLock,
     },
     log::{error, info, trace, warn},
 };
@@ -10,6 +11,8 @@ use {
 // -----------------
 
 pub struct Metadata {
+    // not used yet, but might be handy for truncating paths
+    #[allow(dead_code)]
     pub current_dir: PathBuf,
     pub path: PathBuf,
     pub name: String,
@@ -250,3 +253,17 @@ fn current_exe_path(config: bool) -> Option<PathBuf> {
         },
     };
 }
+
+// brr keeps its log in the state directory, so anything
+// else brr needs to remember between sessions (like goal
+// progress) goes in the same folder. it's needed on every
+// save, so it's only looked up (and created) the first time.
+pub fn get_state_dir() -> Option<PathBuf> {
+    static STATE_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+    STATE_DIR
+    .get_or_init(|| get_conf_or_log_path(false)
+        .and_then(|log_path| log_path.parent().map(Path::to_path_buf))
+    )
+    .clone()
+}
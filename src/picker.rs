@@ -0,0 +1,128 @@
+use crate::{display_width, truncate_to_width};
+
+// -----------------
+
+// something that can be picked, and the byte offset
+// in the file that picking it jumps to
+pub struct PickerItem {
+    pub label: String,
+    // shown on the right, like the line it's on
+    pub detail: String,
+    pub offset: usize,
+}
+
+// a list that covers the screen while a prompt is open. typing
+// in the prompt narrows it down, and the arrow keys pick from
+// what's left.
+pub struct Picker {
+    title: String,
+    items: Vec<PickerItem>,
+    filter: String,
+    // which of the items that match the filter is picked
+    selected: usize,
+    // set if the items can be removed with delete
+    removable: bool,
+    // the offsets of any items that were removed
+    pub removed: Vec<usize>,
+    // the offset of the item that enter was pressed on
+    pub chosen: Option<usize>,
+}
+
+impl Picker {
+    pub fn new(title: &str, items: Vec<PickerItem>, removable: bool) -> Self {
+        Self {
+            title: title.to_string(),
+            items,
+            filter: String::new(),
+            selected: 0,
+            removable,
+            removed: Vec::new(),
+            chosen: None,
+        }
+    }
+
+    fn shown(&self) -> Vec<&PickerItem> {
+        let filter = self.filter.to_lowercase();
+
+        self.items
+        .iter()
+        .filter(|item| item.label.to_lowercase().contains(&filter))
+        .collect()
+    }
+
+    pub fn set_filter(&mut self, filter: &str) {
+        if self.filter != filter {
+            self.filter = filter.to_string();
+            self.selected = 0;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn select_next(&mut self) {
+        let last = self.shown().len().saturating_sub(1);
+
+        self.selected = self.selected.saturating_add(1).min(last);
+    }
+
+    pub fn choose(&mut self) {
+        self.chosen = self.shown().get(self.selected).map(|item| item.offset);
+    }
+
+    pub fn remove(&mut self) {
+        if !self.removable {
+            return
+        }
+        let Some(offset) = self.shown().get(self.selected).map(|item| item.offset) else {
+            return
+        };
+
+        self.items.retain(|item| item.offset != offset);
+        self.removed.push(offset);
+        self.selected = self.selected.min(self.shown().len().saturating_sub(1));
+    }
+
+    // builds the list as lines of text that fit on the screen,
+    // scrolled so the picked item is always on it
+    pub fn lines(&self, width: usize, height: usize) -> Vec<String> {
+        let shown = self.shown();
+        let hint = if self.removable {
+            "up/down to pick, enter to jump, delete to remove, esc to close"
+        } else {
+            "up/down to pick, enter to jump, esc to close"
+        };
+        let mut lines = vec![
+            truncate_to_width(&self.title, width),
+            truncate_to_width(hint, width),
+            String::new(),
+        ];
+        let room = height.saturating_sub(lines.len()).max(1);
+        let first = self.selected.saturating_sub(room.saturating_sub(1));
+
+        if shown.is_empty() {
+            lines.push(String::from("nothing here."));
+        }
+        for (index, item) in shown.iter().enumerate().skip(first).take(room) {
+            let marker = if index == self.selected {
+                "> "
+            } else {
+                "  "
+            };
+            let detail_width = display_width(&item.detail);
+            let label = truncate_to_width(
+                &format!("{marker}{}", item.label),
+                width.saturating_sub(detail_width).saturating_sub(1),
+            );
+            let padding = " ".repeat(width
+                .saturating_sub(display_width(&label))
+                .saturating_sub(detail_width)
+            );
+
+            lines.push(truncate_to_width(&format!("{label}{padding}{}", item.detail), width));
+        }
+
+        lines
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{Metadata, Extension, read_sessions, longest_streak};
+use {
+    std::{
+        collections::BTreeMap,
+        fmt::Write,
+        fs::{read_dir, read_to_string},
+        path::{Path, PathBuf},
+    },
+    chrono::NaiveDate,
+    log::error,
+};
+
+// -----------------
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+// what `brr stats` prints for a single file, or the
+// total for a whole folder of them
+#[derive(Default)]
+struct DocumentStats {
+    name: String,
+    words: usize,
+    chars: usize,
+    sessions: usize,
+    days: BTreeMap<NaiveDate, usize>,
+}
+
+impl DocumentStats {
+    fn from(path: &Path) -> Option<Self> {
+        let content = match read_to_string(path) {
+            Ok(content) => content,
+            Err(error_msg) => {
+                error!("[report.rs]: {error_msg} - could not read {}.", path.display());
+                return None
+            },
+        };
+        let count = words_count::count(&content);
+        let sessions = read_sessions(path);
+        let mut days = BTreeMap::new();
+
+        for session in &sessions {
+            let day_words: &mut usize = days.entry(session.date()).or_default();
+            *day_words = day_words.saturating_add(session.words);
+        }
+
+        Some(Self {
+            name: path.display().to_string(),
+            words: count.words,
+            chars: count.characters,
+            sessions: sessions.len(),
+            days,
+        })
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.words = self.words.saturating_add(other.words);
+        self.chars = self.chars.saturating_add(other.chars);
+        self.sessions = self.sessions.saturating_add(other.sessions);
+
+        for (date, words) in &other.days {
+            let day_words = self.days.entry(*date).or_default();
+            *day_words = day_words.saturating_add(*words);
+        }
+    }
+
+    // only counts days where something was actually written
+    fn writing_days(&self) -> Vec<NaiveDate> {
+        self.days
+        .iter()
+        .filter(|(_, words)| **words > 0)
+        .map(|(date, _)| *date)
+        .collect()
+    }
+
+    #[allow(clippy::cast_precision_loss)] // see sprint.rs
+    fn words_per_day(&self) -> f64 {
+        let writing_days = self.writing_days().len();
+        let words: usize = self.days.values().sum();
+
+        if writing_days > 0 {
+            words as f64 / writing_days as f64
+        } else {
+            0.0
+        }
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = format!(
+            "{}:\n  {} words\n  {} chars\n  {} sessions\n  {:.1} words per day\n  longest streak: {} days\n",
+            self.name,
+            self.words,
+            self.chars,
+            self.sessions,
+            self.words_per_day(),
+            longest_streak(&self.writing_days()),
+        );
+
+        if !self.days.is_empty() {
+            text.push_str("  words written by day:\n");
+            text.extend(self.days
+                .iter()
+                .map(|(date, words)| format!("    {}  {words}\n", date.format(DATE_FORMAT)))
+            );
+        }
+
+        text
+    }
+
+    fn to_json(&self) -> String {
+        let days = self.days
+        .iter()
+        .map(|(date, words)| format!("\"{}\":{words}", date.format(DATE_FORMAT)))
+        .collect::<Vec<String>>()
+        .join(",");
+
+        format!(
+            "{{\"name\":{},\"words\":{},\"characters\":{},\"sessions\":{},\"words_per_day\":{:.1},\"longest_streak\":{},\"days\":{{{days}}}}}",
+            json_string(&self.name),
+            self.words,
+            self.chars,
+            self.sessions,
+            self.words_per_day(),
+            longest_streak(&self.writing_days()),
+        )
+    }
+}
+
+// prints stats for a file, or for every .txt and .md
+// file in a folder (plus a total for all of them)
+pub fn report(target: &Path, json: bool) -> Result<String, String> {
+    let mut files = Vec::new();
+
+    if target.is_dir() {
+        let mut paths: Vec<PathBuf> = match read_dir(target) {
+            Ok(entries) => entries.flatten().map(|entry| entry.path()).collect(),
+            Err(error_msg) => {
+                error!("[report.rs]: {error_msg} - could not read {}.", target.display());
+                return Err(format!("error: could not read {}. see log for details.", target.display()))
+            },
+        };
+        paths.sort();
+
+        for path in paths {
+            let extension = Metadata::get_extension(&path);
+
+            if path.is_file()
+            && (extension == Extension::Md || extension == Extension::Txt) {
+                files.extend(DocumentStats::from(&path));
+            }
+        }
+    } else if let Some(stats) = DocumentStats::from(target) {
+        files.push(stats);
+    } else {
+        return Err(format!("error: could not read {}. see log for details.", target.display()))
+    }
+
+    let mut total = DocumentStats {
+        name: String::from("total"),
+        ..Default::default()
+    };
+    for stats in &files {
+        total.add(stats);
+    }
+
+    if json {
+        let files_json = files
+        .iter()
+        .map(DocumentStats::to_json)
+        .collect::<Vec<String>>()
+        .join(",");
+
+        Ok(format!("{{\"files\":[{files_json}],\"total\":{}}}", total.to_json()))
+    } else if files.len() == 1 {
+        Ok(files
+            .iter()
+            .map(DocumentStats::to_text)
+            .collect()
+        )
+    } else {
+        Ok(files
+            .iter()
+            .chain([&total])
+            .map(DocumentStats::to_text)
+            .collect::<Vec<String>>()
+            .join("\n")
+        )
+    }
+}
+
+fn json_string(to_escape: &str) -> String {
+    let mut escaped = String::from("\"");
+
+    for character in to_escape.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            control if control.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", u32::from(control));
+            },
+            other => escaped.push(other),
+        }
+    }
+    escaped.push('"');
+
+    escaped
+}
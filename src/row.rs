@@ -1,3 +1,7 @@
+use crate::Span;
+
+// -----------------
+
 // despite the repetition, i think this makes
 // the code more readable overall
 #[allow(clippy::module_name_repetitions)]
@@ -6,6 +10,21 @@ pub struct DisplayRow {
     pub content: String,
     pub len: usize,
     pub line_no: usize,
+    // true if a word was too long to fit on this row
+    // and had to be broken up onto the next one
+    pub broken: bool,
+    // true if the row ends partway through a word that was
+    // hyphenated. the hyphen itself is counted in len, but it
+    // isn't in content, since it isn't in the file either.
+    pub hyphenated: bool,
+    // how many columns of spaces the row is pushed in by,
+    // see hanging_indent(). this is counted in len too.
+    pub indent: usize,
+    // true if the line the row is part of goes right to left
+    pub rtl: bool,
+    // markdown styling for the row. if this is empty, the
+    // row is just printed as it is.
+    pub spans: Vec<Span>,
 }
 
 
@@ -15,6 +34,11 @@ impl From<(String, usize, usize)> for DisplayRow {
             content: line,
             len,
             line_no,
+            broken: false,
+            hyphenated: false,
+            indent: 0,
+            rtl: false,
+            spans: Vec::new(),
         }
     }
 }
@@ -0,0 +1,147 @@
+use {
+    std::ops::Range,
+    regex::{Regex, RegexBuilder, escape},
+};
+
+// -----------------
+
+// a match, as a byte range of the line it's on
+#[derive(Clone)]
+pub struct Match {
+    pub line_no: usize,
+    pub range: Range<usize>,
+}
+
+// what's being searched for in view mode, and everywhere it was
+// found. the options stay the same from one search to the next.
+#[allow(clippy::struct_excessive_bools)] // see config.rs
+#[derive(Default)]
+pub struct Search {
+    pub query: String,
+    pub ignore_case: bool,
+    pub whole_word: bool,
+    pub regex: bool,
+    pub matches: Vec<Match>,
+    // the match the view was last moved to
+    pub current: usize,
+    // set if the query isn't a valid regex
+    pub invalid: bool,
+    // true from when the search prompt opens until the search
+    // is cleared, so the options show in the status bar
+    pub active: bool,
+}
+
+impl Search {
+    // finds every match of query in text, a line at a time, so
+    // a match can't go over a line break
+    pub fn find(&mut self, query: &str, text: &str) {
+        self.query = query.to_string();
+        self.matches.clear();
+        self.current = 0;
+        self.invalid = false;
+
+        if query.is_empty() {
+            return
+        }
+        let Some(pattern) = self.pattern() else {
+            self.invalid = true;
+            return
+        };
+
+        for (index, line) in text.lines().enumerate() {
+            let line_no = index.saturating_add(1);
+
+            self.matches.extend(pattern
+                .find_iter(line)
+                .filter(|found| !found.is_empty())
+                .filter(|found| !self.whole_word || is_whole_word(line, found.range()))
+                .map(|found| Match {
+                    line_no,
+                    range: found.range(),
+                })
+            );
+        }
+    }
+
+    fn pattern(&self) -> Option<Regex> {
+        let pattern = if self.regex {
+            self.query.clone()
+        } else {
+            escape(&self.query)
+        };
+
+        RegexBuilder::new(&pattern)
+        .case_insensitive(self.ignore_case)
+        .build()
+        .ok()
+    }
+
+    pub fn clear(&mut self) {
+        self.query.clear();
+        self.matches.clear();
+        self.current = 0;
+        self.invalid = false;
+        self.active = false;
+    }
+
+    // the first match at or after line_no, or the first one
+    // in the document if there aren't any after it
+    pub fn first_from(&mut self, line_no: usize) -> Option<&Match> {
+        self.current = self.matches
+        .iter()
+        .position(|found| found.line_no >= line_no)
+        .unwrap_or(0);
+
+        self.matches.get(self.current)
+    }
+
+    // moves to the next (or previous) match, going
+    // around to the other end after the last one
+    pub fn step(&mut self, forward: bool) -> Option<&Match> {
+        if self.matches.is_empty() {
+            return None
+        }
+        self.current = if forward {
+            self.current.saturating_add(1) % self.matches.len()
+        } else {
+            self.current.checked_sub(1).unwrap_or(self.matches.len().saturating_sub(1))
+        };
+
+        self.matches.get(self.current)
+    }
+
+    // the matches on a line, as byte ranges of it
+    pub fn on_line(&self, line_no: usize) -> impl Iterator<Item = &Range<usize>> {
+        let start = self.matches.partition_point(|found| found.line_no < line_no);
+
+        self.matches
+        .get(start..)
+        .unwrap_or_default()
+        .iter()
+        .take_while(move |found| found.line_no == line_no)
+        .map(|found| &found.range)
+    }
+
+    // which options are on, for the status bar
+    pub fn options(&self) -> String {
+        [
+            (self.ignore_case, "ignore case"),
+            (self.whole_word, "whole word"),
+            (self.regex, "regex"),
+        ]
+        .iter()
+        .filter(|(on, _)| *on)
+        .flat_map(|(_, name)| ["[", name, "] "])
+        .collect()
+    }
+}
+
+// a whole word match doesn't have letters or numbers
+// right before or after it
+fn is_whole_word(line: &str, range: Range<usize>) -> bool {
+    let is_word = |character: char| character.is_alphanumeric() || character == '_';
+    let before = line.get(..range.start).and_then(|before| before.chars().last());
+    let after = line.get(range.end..).and_then(|after| after.chars().next());
+
+    !before.is_some_and(is_word) && !after.is_some_and(is_word)
+}
@@ -0,0 +1,81 @@
+use {
+    std::time::{Duration, Instant},
+    chrono::{DateTime, Local},
+};
+
+// -----------------
+
+// a timed writing sprint. words and saves are stored as they
+// were when the sprint started so we can work out how much
+// was written during it when it's over.
+pub struct Sprint {
+    started_at: DateTime<Local>,
+    start: Instant,
+    length: Duration,
+    start_words: usize,
+    start_saves: usize,
+}
+
+pub struct SprintResult {
+    pub started_at: DateTime<Local>,
+    pub minutes: f64,
+    pub words: usize,
+    pub words_per_minute: f64,
+    pub saves: usize,
+}
+
+impl Sprint {
+    pub fn start(minutes: usize, start_words: usize, start_saves: usize) -> Self {
+        Self {
+            started_at: Local::now(),
+            start: Instant::now(),
+            length: Duration::from_secs((minutes as u64).saturating_mul(60)),
+            start_words,
+            start_saves,
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.start.elapsed() >= self.length
+    }
+
+    // e.g. "sprint 24:59"
+    pub fn countdown(&self) -> String {
+        let remaining = self.length.saturating_sub(self.start.elapsed()).as_secs();
+
+        format!("sprint {:02}:{:02} ", remaining / 60, remaining % 60)
+    }
+
+    // sprints can be ended early, so the time is however long
+    // it actually ran for rather than the length it was set to
+    #[allow(clippy::cast_precision_loss)] // nobody's writing 2^52 words in one sprint
+    pub fn finish(&self, end_words: usize, end_saves: usize) -> SprintResult {
+        let minutes = self.start.elapsed().min(self.length).as_secs_f64() / 60.0;
+        let words = end_words.saturating_sub(self.start_words);
+        let words_per_minute = if minutes > 0.0 {
+            words as f64 / minutes
+        } else {
+            0.0
+        };
+
+        SprintResult {
+            started_at: self.started_at,
+            minutes,
+            words,
+            words_per_minute,
+            saves: end_saves.saturating_sub(self.start_saves),
+        }
+    }
+}
+
+impl SprintResult {
+    pub fn message(&self) -> String {
+        format!(
+            "sprint over! {} words in {:.0} min ({:.1} wpm), {} saves.",
+            self.words,
+            self.minutes,
+            self.words_per_minute,
+            self.saves,
+        )
+    }
+}
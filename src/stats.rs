@@ -0,0 +1,411 @@
+use crate::{get_state_dir, SprintResult};
+use {
+    std::{
+        path::{Path, PathBuf},
+        fs::{File, OpenOptions, create_dir_all},
+        io::{BufReader, BufRead, Write, Error},
+    },
+    chrono::{DateTime, FixedOffset, Local, NaiveDate},
+    log::{error, info},
+};
+
+// -----------------
+
+const PROGRESS_FILE: &str = "progress";
+const SPRINTS_FILE: &str = "sprints";
+const DOCUMENTS_DIR: &str = "documents";
+const SESSIONS_EXT: &str = "sessions";
+const SAVES_EXT: &str = "saves";
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+// keeps track of how many words were written on each day,
+// across every file and every session. it's stored in the
+// state directory as plain text, one "date words" pair per
+// save, so it can be read (or fixed) by hand if need be.
+// the pairs for each day are added up when it's read.
+#[derive(Default)]
+pub struct Progress {
+    path: Option<PathBuf>,
+    days: Vec<(NaiveDate, usize)>,
+}
+
+impl Progress {
+    pub fn load() -> Self {
+        let path = get_state_dir().map(|mut dir| {
+            dir.push(PROGRESS_FILE);
+            dir
+        });
+        let days = path.as_deref().map(read_days).unwrap_or_default();
+
+        Self {
+            path,
+            days,
+        }
+    }
+
+    // every day anything was written, for the calendar
+    pub fn days(&self) -> &[(NaiveDate, usize)] {
+        &self.days
+    }
+
+    pub fn today(&self) -> usize {
+        let today = Local::now().date_naive();
+
+        self.days
+        .iter()
+        .find(|(date, _)| *date == today)
+        .map_or(0, |(_, words)| *words)
+    }
+
+    // add words to today's total and write them out straight
+    // away, so nothing is lost if brr doesn't quit cleanly. the
+    // words are appended rather than rewriting the whole file,
+    // so two brrs open at once don't write over each other, and
+    // the file is read again to pick up what the other one wrote.
+    pub fn add_today(&mut self, words: usize) {
+        if words == 0 {
+            return
+        }
+        let today = Local::now().date_naive();
+        let Some(path) = &self.path else {
+            add_words(&mut self.days, today, words);
+            return
+        };
+
+        if let Err(error_msg) = append_to(path, &format!("{} {words}", today.format(DATE_FORMAT))) {
+            error!("[stats.rs]: {error_msg} - could not write progress file.");
+            add_words(&mut self.days, today, words);
+        } else {
+            self.days = read_days(path);
+        }
+    }
+}
+
+fn read_days(path: &Path) -> Vec<(NaiveDate, usize)> {
+    let mut days = Vec::new();
+
+    match File::open(path) {
+        Ok(progress_file) => {
+            for (line_index, file_line) in BufReader::new(progress_file).lines().enumerate() {
+                if let Some((date, words)) = file_line.ok().as_deref().and_then(parse_day) {
+                    add_words(&mut days, date, words);
+                } else {
+                    error!("[stats.rs]: could not read progress file line {}. skipping it.", line_index.saturating_add(1));
+                }
+            }
+        },
+        Err(error_msg) => {
+            info!("[stats.rs]: {error_msg} - no progress file yet at path {}", path.display());
+        },
+    }
+
+    days
+}
+
+fn add_words(days: &mut Vec<(NaiveDate, usize)>, date: NaiveDate, words: usize) {
+    if let Some((_, day_words)) = days.iter_mut().find(|(day, _)| *day == date) {
+        *day_words = day_words.saturating_add(words);
+    } else {
+        days.push((date, words));
+    }
+}
+
+// everything brr knows about a finished editing session of
+// a single file. written as "key=value" pairs on one line in
+// the file's sessions file, so new fields can be added later.
+pub struct SessionRecord {
+    pub started_at: DateTime<FixedOffset>,
+    pub ended_at: DateTime<FixedOffset>,
+    pub words: usize,
+    pub chars: usize,
+    // byte offset in the file where the session started writing
+    pub start_offset: usize,
+    pub keystrokes: usize,
+    pub backspaces: usize,
+    pub words_per_minute: f64,
+    pub longest_pause: u64,
+    pub bursts: usize,
+    pub longest_burst: usize,
+}
+
+impl SessionRecord {
+    // unknown keys are skipped, missing ones are left at zero,
+    // but a session without valid times isn't much use
+    fn from_line(line: &str) -> Option<Self> {
+        let mut started_at = None;
+        let mut ended_at = None;
+        let mut record = Self {
+            started_at: DateTime::default(),
+            ended_at: DateTime::default(),
+            words: 0,
+            chars: 0,
+            start_offset: 0,
+            keystrokes: 0,
+            backspaces: 0,
+            words_per_minute: 0.0,
+            longest_pause: 0,
+            bursts: 0,
+            longest_burst: 0,
+        };
+
+        for (key, value) in line.split(' ').filter_map(|pair| pair.split_once('=')) {
+            match key {
+                "started" => started_at = DateTime::parse_from_rfc3339(value).ok(),
+                "ended" => ended_at = DateTime::parse_from_rfc3339(value).ok(),
+                "words" => record.words = value.parse().unwrap_or(0),
+                "chars" => record.chars = value.parse().unwrap_or(0),
+                "offset" => record.start_offset = value.parse().unwrap_or(0),
+                "keys" => record.keystrokes = value.parse().unwrap_or(0),
+                "backspaces" => record.backspaces = value.parse().unwrap_or(0),
+                "wpm" => record.words_per_minute = value.parse().unwrap_or(0.0),
+                "pause" => record.longest_pause = value.parse().unwrap_or(0),
+                "bursts" => record.bursts = value.parse().unwrap_or(0),
+                "longest-burst" => record.longest_burst = value.parse().unwrap_or(0),
+                _ => (),
+            }
+        }
+
+        record.started_at = started_at?;
+        record.ended_at = ended_at?;
+        Some(record)
+    }
+
+    pub fn date(&self) -> NaiveDate {
+        self.started_at.date_naive()
+    }
+
+    fn to_line(&self) -> String {
+        format!(
+            "started={} ended={} words={} chars={} offset={} keys={} backspaces={} wpm={:.1} pause={} bursts={} longest-burst={}",
+            self.started_at.to_rfc3339(),
+            self.ended_at.to_rfc3339(),
+            self.words,
+            self.chars,
+            self.start_offset,
+            self.keystrokes,
+            self.backspaces,
+            self.words_per_minute,
+            self.longest_pause,
+            self.bursts,
+            self.longest_burst,
+        )
+    }
+}
+
+pub fn read_sessions(document_path: &Path) -> Vec<SessionRecord> {
+    document_stats_path(document_path, SESSIONS_EXT)
+    .map(|path| read_sessions_file(&path))
+    .unwrap_or_default()
+}
+
+fn read_sessions_file(path: &Path) -> Vec<SessionRecord> {
+    let mut sessions = Vec::new();
+
+    if let Ok(sessions_file) = File::open(path) {
+        for (line_index, file_line) in BufReader::new(sessions_file).lines().enumerate() {
+            if let Some(record) = file_line.ok().as_deref().and_then(SessionRecord::from_line) {
+                sessions.push(record);
+            } else {
+                error!("[stats.rs]: could not read line {} of {}. skipping it.", line_index.saturating_add(1), path.display());
+            }
+        }
+    }
+
+    sessions
+}
+
+// counts the most consecutive days in a row. expects
+// the dates to be sorted and without duplicates.
+pub fn longest_streak(dates: &[NaiveDate]) -> usize {
+    let mut longest = 0;
+    let mut current: usize = 0;
+    let mut previous: Option<NaiveDate> = None;
+
+    for date in dates {
+        if previous.and_then(|day| day.succ_opt()) == Some(*date) {
+            current = current.saturating_add(1);
+        } else {
+            current = 1;
+        }
+        longest = usize::max(longest, current);
+        previous = Some(*date);
+    }
+
+    longest
+}
+
+// counts back from today. if nothing's been written yet
+// today, the streak isn't broken until tomorrow, so count
+// back from yesterday instead.
+pub fn current_streak(dates: &[NaiveDate], today: NaiveDate) -> usize {
+    let mut day = if dates.contains(&today) {
+        Some(today)
+    } else {
+        today.pred_opt()
+    };
+    let mut streak: usize = 0;
+
+    while let Some(date) = day.filter(|date| dates.contains(date)) {
+        streak = streak.saturating_add(1);
+        day = date.pred_opt();
+    }
+
+    streak
+}
+
+pub fn log_session(record: &SessionRecord, document_path: &Path) {
+    let result = document_stats_path(document_path, SESSIONS_EXT)
+    .map_or(Ok(()), |path| append_to(&path, &record.to_line()));
+
+    if let Err(error_msg) = result {
+        error!("[stats.rs]: {error_msg} - could not log session.");
+    }
+}
+
+// a save that added text to a file: the bytes from start to end
+// were written at saved_at. kept so the time gutter can show when
+// each line was written.
+#[derive(Clone, Copy)]
+pub struct SaveRecord {
+    pub start: usize,
+    pub end: usize,
+    pub saved_at: DateTime<FixedOffset>,
+}
+
+impl SaveRecord {
+    fn from_line(line: &str) -> Option<Self> {
+        let mut start = None;
+        let mut end = None;
+        let mut saved_at = None;
+
+        for (key, value) in line.split(' ').filter_map(|pair| pair.split_once('=')) {
+            match key {
+                "start" => start = value.parse().ok(),
+                "end" => end = value.parse().ok(),
+                "saved" => saved_at = DateTime::parse_from_rfc3339(value).ok(),
+                _ => (),
+            }
+        }
+
+        Some(Self {
+            start: start?,
+            end: end?,
+            saved_at: saved_at?,
+        })
+    }
+
+    fn to_line(self) -> String {
+        format!(
+            "start={} end={} saved={}",
+            self.start,
+            self.end,
+            self.saved_at.to_rfc3339(),
+        )
+    }
+}
+
+pub fn read_saves(document_path: &Path) -> Vec<SaveRecord> {
+    let mut saves = Vec::new();
+    let Some(path) = document_stats_path(document_path, SAVES_EXT) else {
+        return saves
+    };
+
+    if let Ok(saves_file) = File::open(&path) {
+        for (line_index, file_line) in BufReader::new(saves_file).lines().enumerate() {
+            if let Some(record) = file_line.ok().as_deref().and_then(SaveRecord::from_line) {
+                saves.push(record);
+            } else {
+                error!("[stats.rs]: could not read line {} of {}. skipping it.", line_index.saturating_add(1), path.display());
+            }
+        }
+    }
+
+    saves
+}
+
+pub fn log_save(record: SaveRecord, document_path: &Path) {
+    let result = document_stats_path(document_path, SAVES_EXT)
+    .map_or(Ok(()), |path| append_to(&path, &record.to_line()));
+
+    if let Err(error_msg) = result {
+        error!("[stats.rs]: {error_msg} - could not log save.");
+    }
+}
+
+// every file gets its own stats files in the state directory,
+// named after the file plus a hash of its full path so that
+// two files with the same name in different folders don't mix
+pub fn document_stats_path(document_path: &Path, extension: &str) -> Option<PathBuf> {
+    let full_path = document_path
+    .canonicalize()
+    .unwrap_or_else(|_| document_path.to_path_buf());
+    let name = full_path
+    .file_name()
+    .map_or(String::from("untitled"), |name| name.to_string_lossy().to_string());
+    let mut path = get_state_dir()?;
+    path.push(DOCUMENTS_DIR);
+
+    if let Err(error_msg) = create_dir_all(&path) {
+        error!("[stats.rs]: {error_msg} - could not create documents directory {}.", path.display());
+        return None
+    }
+
+    path.push(format!("{name}-{:016x}.{extension}", hash_path(&full_path)));
+    Some(path)
+}
+
+// FNV-1a. std's hasher isn't guaranteed to stay the same
+// between rust versions, and these names need to.
+fn hash_path(path: &Path) -> u64 {
+    path
+    .to_string_lossy()
+    .bytes()
+    .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+// one line per sprint: start time, minutes, words,
+// words per minute, saves and the file it was in.
+pub fn log_sprint(result: &SprintResult, file_name: &str) {
+    let line = format!(
+        "{} {:.1} {} {:.1} {} {file_name}",
+        result.started_at.to_rfc3339(),
+        result.minutes,
+        result.words,
+        result.words_per_minute,
+        result.saves,
+    );
+
+    if let Err(error_msg) = append_line(SPRINTS_FILE, &line) {
+        error!("[stats.rs]: {error_msg} - could not log sprint.");
+    }
+}
+
+fn append_line(file_name: &str, line: &str) -> Result<(), Error> {
+    let Some(mut path) = get_state_dir() else {
+        return Ok(())
+    };
+    path.push(file_name);
+
+    append_to(&path, line)
+}
+
+fn append_to(path: &Path, line: &str) -> Result<(), Error> {
+    let mut stats_file = OpenOptions::new()
+    .create(true)
+    .append(true)
+    .open(path)?;
+
+    writeln!(stats_file, "{line}")?;
+    Ok(())
+}
+
+fn parse_day(line: &str) -> Option<(NaiveDate, usize)> {
+    let (date, words) = line.split_once(' ')?;
+
+    Some((
+        NaiveDate::parse_from_str(date, DATE_FORMAT).ok()?,
+        words.trim().parse().ok()?,
+    ))
+}
@@ -0,0 +1,24 @@
+// -----------------
+
+// how a piece of text should look on screen. markdown rendering
+// sets these, and terminal.rs turns them into attributes.
+// see config.rs for why this is a bunch of bools.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct Style {
+    pub bold: bool,
+    pub italic: bool,
+    pub code: bool,
+    // the > at the start of a block quote, drawn as a bar
+    pub quote: bool,
+    // markdown syntax, like # or **, which can be dimmed
+    pub marker: bool,
+}
+
+// a styled piece of a display row, as byte offsets into its content
+#[derive(Clone, Copy)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub style: Style,
+}
@@ -1,15 +1,19 @@
-use crate::{Position, die,};
+use crate::{Position, Style, Theme, Part, die,};
 use {
-    std::io::{
-        Write,
-        Stdout,
-        stdout,
-        Error,
+    std::{
+        io::{
+            Write,
+            Stdout,
+            stdout,
+            Error,
+        },
+        mem::take,
     },
     crossterm::{
         terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType, size, LeaveAlternateScreen, EnterAlternateScreen},
-        cursor::{MoveToNextLine, MoveTo, Hide, Show, SetCursorStyle},
-        style::{Print, SetAttribute, Attribute::{Reverse, NoReverse}},
+        cursor::{MoveTo, MoveToColumn, Hide, Show, SetCursorStyle},
+        event::{EnableMouseCapture, DisableMouseCapture},
+        style::{Print, SetAttribute, SetForegroundColor, SetBackgroundColor, ResetColor, Color, Attribute::{Reset, Reverse, NoReverse, Underlined, Bold, Dim, NormalIntensity, Italic, NoItalic}},
         ExecutableCommand, 
         QueueableCommand,
     },
@@ -18,10 +22,20 @@ use {
 
 // -----------------
 
+// rows aren't written to the terminal straight away. each row is
+// drawn into a buffer first, and only written out if it's different
+// from what was drawn on that row last time, so a keypress only
+// redraws the rows it actually changed.
 pub struct Terminal {
     pub stdout: Stdout,
     pub width: usize,
     pub height: usize,
+    pub theme: Theme,
+    last_frame: Vec<Vec<u8>>,
+    row: Vec<u8>,
+    row_index: usize,
+    // everything waiting to be written on the next flush
+    output: Vec<u8>,
 }
 
 impl Terminal {
@@ -32,6 +46,11 @@ impl Terminal {
             stdout: stdout(),
             width: columns,
             height: rows.saturating_sub(2),
+            theme: Theme::default(),
+            last_frame: Vec::new(),
+            row: Vec::new(),
+            row_index: 0,
+            output: Vec::new(),
         }
     }
 
@@ -61,7 +80,10 @@ impl Terminal {
         stdout().queue(Hide)?;
         stdout().queue(MoveTo(0, 0))?;
         stdout().queue(Clear(ClearType::All))?;
+        stdout().queue(SetAttribute(Reset))?;
+        stdout().queue(ResetColor)?;
         stdout().queue(SetCursorStyle::DefaultUserShape)?;
+        stdout().queue(DisableMouseCapture)?;
         stdout().queue(Show)?;
         stdout().queue(LeaveAlternateScreen)?;
         stdout().queue(Print(quit_msg))?;
@@ -70,70 +92,144 @@ impl Terminal {
         Ok(())
     }
 
+    // moving the cursor finishes whatever row was being drawn,
+    // and anything drawn after this goes on the row it moved to
     #[allow(clippy::cast_possible_truncation)]
     pub fn move_cursor(&mut self, position: &Position) -> Result<(), Error> {
-        let Position{mut x, mut y} = position;
-        // add 1 to change from 0-indexing to the 1-indexing that
-        // terminals use
-        x = x.saturating_add(1);
-        y = y.saturating_add(1);
-        let new_x = x;
-        let new_y = y;
-        self.stdout.queue(MoveTo(
-            new_x.saturating_sub(1) as u16,
-            new_y.saturating_sub(1) as u16
-        ))?;
+        self.finish_row()?;
+        self.row_index = position.y;
+        self.output.queue(MoveTo(position.x as u16, position.y as u16))?;
         Ok(())
     }
 
     pub fn clear_line(&mut self) -> Result<(), Error> {
-        self.stdout.queue(Clear(ClearType::UntilNewLine))?;
+        self.row.queue(Clear(ClearType::UntilNewLine))?;
         Ok(())
     }
 
     pub fn new_line(&mut self) -> Result<(), Error> {
-        self.stdout.queue(MoveToNextLine(1))?;
+        self.finish_row()?;
+        self.row_index = self.row_index.saturating_add(1);
+        Ok(())
+    }
+
+    // writes the row out if it changed since the last frame.
+    // rows that are written get moved to and start from the text
+    // style, since the rows before them might not have been.
+    #[allow(clippy::cast_possible_truncation)] // see move_cursor()
+    fn finish_row(&mut self) -> Result<(), Error> {
+        if self.row.is_empty() {
+            return Ok(())
+        }
+        let row = take(&mut self.row);
+
+        if self.last_frame.get(self.row_index) != Some(&row) {
+            self.output.queue(MoveTo(0, self.row_index as u16))?;
+            queue_part(&mut self.output, &self.theme, Part::Text)?;
+            self.output.extend_from_slice(&row);
+
+            if self.last_frame.len() <= self.row_index {
+                self.last_frame.resize(self.row_index.saturating_add(1), Vec::new());
+            }
+            if let Some(last_row) = self.last_frame.get_mut(self.row_index) {
+                *last_row = row;
+            }
+        }
         Ok(())
     }
 
     pub fn queue_print(&mut self, to_print: &str) -> Result<(), Error> {
-        self.stdout.queue(Print(to_print))?;
+        self.row.queue(Print(to_print))?;
         Ok(())
     }
 
-    pub fn queue_print_reversed(&mut self, to_print: &str) -> Result<(), Error> {
-        self.stdout.queue(SetAttribute(Reverse))?;
-        self.stdout.queue(Print(to_print))?;
-        self.stdout.queue(SetAttribute(NoReverse))?;
+    // prints something in the theme's style for that part of
+    // the screen, then goes back to the style for text
+    pub fn queue_print_as(&mut self, to_print: &str, part: Part) -> Result<(), Error> {
+        self.set_part(part)?;
+        self.row.queue(Print(to_print))?;
+        self.set_part(Part::Text)?;
         Ok(())
     }
 
-    pub fn cursor_hide(&mut self) -> Result<(), Error> {
-        self.stdout.queue(Hide)?;
+    // prints something at a column of the row, whatever's
+    // already been printed on it
+    #[allow(clippy::cast_possible_truncation)] // see move_cursor()
+    pub fn queue_print_at(&mut self, to_print: &str, column: usize, part: Part) -> Result<(), Error> {
+        self.row.queue(MoveToColumn(column as u16))?;
+        self.queue_print_as(to_print, part)
+    }
+
+    // mouse events are only sent to brr while this is on, so
+    // the terminal can still select text the rest of the time
+    pub fn set_mouse_capture(capture: bool) -> Result<(), Error> {
+        if capture {
+            stdout().execute(EnableMouseCapture)?;
+        } else {
+            stdout().execute(DisableMouseCapture)?;
+        }
         Ok(())
     }
 
-    pub fn cursor_show(&mut self) -> Result<(), Error> {
-        self.stdout.queue(Show)?;
+    pub fn queue_print_dimmed(&mut self, to_print: &str, part: Part) -> Result<(), Error> {
+        self.set_part(part)?;
+        self.row.queue(SetAttribute(Dim))?;
+        self.row.queue(Print(to_print))?;
+        self.set_part(Part::Text)?;
         Ok(())
     }
 
-    pub fn flush(&mut self) -> Result<(), Error> {
-        self.stdout.flush()?;
+    // everything printed on the row after this is in the
+    // theme's style for that part of the screen
+    pub fn set_part(&mut self, part: Part) -> Result<(), Error> {
+        queue_part(&mut self.row, &self.theme, part)
+    }
+
+    pub fn queue_print_styled(&mut self, to_print: &str, style: Style, dim_markers: bool) -> Result<(), Error> {
+        if style.bold {
+            self.row.queue(SetAttribute(Bold))?;
+        }
+        if style.marker && dim_markers {
+            self.row.queue(SetAttribute(Dim))?;
+        }
+        if style.italic {
+            self.row.queue(SetAttribute(Italic))?;
+        }
+        if style.code {
+            self.row.queue(SetAttribute(Reverse))?;
+        }
+        self.row.queue(Print(to_print))?;
+        self.row.queue(SetAttribute(NormalIntensity))?;
+        self.row.queue(SetAttribute(NoItalic))?;
+        self.row.queue(SetAttribute(NoReverse))?;
         Ok(())
     }
 
-    pub fn reverse_colors(&mut self) -> Result<(), Error>{
-        self.stdout.queue(SetAttribute(Reverse))?;
+    pub fn bell(&mut self) -> Result<(), Error> {
+        self.output.queue(Print('\u{7}'))?;
         Ok(())
     }
 
-    pub fn no_reverse_colors(&mut self) -> Result<(), Error>{
-        self.stdout.queue(SetAttribute(NoReverse))?;
+    pub fn cursor_hide(&mut self) -> Result<(), Error> {
+        self.output.queue(Hide)?;
         Ok(())
     }
 
+    pub fn cursor_show(&mut self) -> Result<(), Error> {
+        self.output.queue(Show)?;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.finish_row()?;
+        self.stdout.write_all(&take(&mut self.output))?;
+        self.stdout.flush()?;
+        Ok(())
+    }
+
+    // the screen is empty now, so everything has to be redrawn
     pub fn clear_all(&mut self) -> Result<(), Error> {
+        self.last_frame.clear();
         self.stdout.execute(Clear(ClearType::All))?;
         Ok(())
     }
@@ -148,3 +244,21 @@ impl Terminal {
         Ok(())
     }
 }
+
+// anything a part of the theme doesn't set a colour for
+// falls back to the text colours
+fn queue_part(writer: &mut Vec<u8>, theme: &Theme, part: Part) -> Result<(), Error> {
+    let text = theme.text;
+    let element = theme.element(part);
+
+    writer.queue(SetAttribute(Reset))?;
+    writer.queue(SetForegroundColor(element.fg.or(text.fg).unwrap_or(Color::Reset)))?;
+    writer.queue(SetBackgroundColor(element.bg.or(text.bg).unwrap_or(Color::Reset)))?;
+    if element.reverse {
+        writer.queue(SetAttribute(Reverse))?;
+    }
+    if element.underline {
+        writer.queue(SetAttribute(Underlined))?;
+    }
+    Ok(())
+}
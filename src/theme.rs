@@ -0,0 +1,267 @@
+use {
+    std::env::var_os,
+    crossterm::style::Color,
+};
+
+// -----------------
+
+// the options that can be set on their own in brr.conf,
+// on top of whichever theme is picked
+pub const THEME_KEYS: [&str; 13] = [
+    "text-fg", "text-bg",
+    "buffer-fg", "buffer-bg", "buffer-style",
+    "gutter-fg", "gutter-bg",
+    "status-fg", "status-bg",
+    "message-fg", "message-bg",
+    "match-fg", "match-bg",
+];
+
+// the parts of the screen a theme can colour in
+#[derive(Clone, Copy)]
+pub enum Part {
+    Text,
+    Buffer,
+    Gutter,
+    StatusBar,
+    Message,
+    // search matches in view mode
+    Match,
+}
+
+// how one part of the screen looks. a colour of none means
+// the part just uses the text colours (or the terminal's own
+// colours, if the text doesn't have any either).
+#[derive(Clone, Copy, Default)]
+pub struct Element {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub reverse: bool,
+    pub underline: bool,
+}
+
+#[derive(Clone, Copy)]
+pub struct Theme {
+    pub text: Element,
+    pub buffer: Element,
+    pub gutter: Element,
+    pub status_bar: Element,
+    pub message: Element,
+    pub matched: Element,
+}
+
+// this is how brr has always looked, so it works on any
+// terminal: everything but the text is in reverse video
+impl Default for Theme {
+    fn default() -> Self {
+        let reversed = Element {
+            reverse: true,
+            ..Default::default()
+        };
+
+        Self {
+            text: Element::default(),
+            buffer: reversed,
+            gutter: reversed,
+            status_bar: reversed,
+            message: Element::default(),
+            matched: reversed,
+        }
+    }
+}
+
+impl Theme {
+    pub fn preset(name: &str) -> Option<Self> {
+        let theme = match name {
+            "default" => Self::default(),
+            "paper" => Self {
+                text: colors(rgb(59, 50, 40), rgb(245, 239, 224)),
+                buffer: underlined(rgb(122, 78, 45)),
+                gutter: colors(rgb(168, 159, 145), rgb(235, 227, 208)),
+                status_bar: colors(rgb(245, 239, 224), rgb(92, 80, 64)),
+                message: colors(rgb(92, 80, 64), None),
+                matched: colors(rgb(59, 50, 40), rgb(240, 208, 128)),
+            },
+            "night" => Self {
+                text: colors(rgb(200, 200, 200), rgb(28, 28, 36)),
+                buffer: underlined(rgb(143, 188, 187)),
+                gutter: colors(rgb(90, 90, 110), rgb(36, 36, 46)),
+                status_bar: colors(rgb(28, 28, 36), rgb(143, 161, 179)),
+                message: colors(rgb(143, 161, 179), None),
+                matched: colors(rgb(28, 28, 36), rgb(235, 203, 139)),
+            },
+            "solarized-dark" => Self {
+                text: colors(rgb(131, 148, 150), rgb(0, 43, 54)),
+                buffer: colors(rgb(181, 137, 0), None),
+                gutter: colors(rgb(88, 110, 117), rgb(7, 54, 66)),
+                status_bar: colors(rgb(0, 43, 54), rgb(131, 148, 150)),
+                message: colors(rgb(42, 161, 152), None),
+                matched: colors(rgb(0, 43, 54), rgb(181, 137, 0)),
+            },
+            "solarized-light" => Self {
+                text: colors(rgb(101, 123, 131), rgb(253, 246, 227)),
+                buffer: colors(rgb(203, 75, 22), None),
+                gutter: colors(rgb(147, 161, 161), rgb(238, 232, 213)),
+                status_bar: colors(rgb(253, 246, 227), rgb(101, 123, 131)),
+                message: colors(rgb(38, 139, 210), None),
+                matched: colors(rgb(253, 246, 227), rgb(181, 137, 0)),
+            },
+            // only uses the 16 colours every terminal has
+            "basic" => Self {
+                text: Element::default(),
+                buffer: colors(Some(Color::Cyan), None),
+                gutter: colors(Some(Color::DarkGrey), None),
+                status_bar: colors(Some(Color::Black), Some(Color::Grey)),
+                message: colors(Some(Color::Yellow), None),
+                matched: colors(Some(Color::Black), Some(Color::Yellow)),
+            },
+            _ => return None,
+        };
+
+        Some(theme)
+    }
+
+    pub fn element(&self, part: Part) -> Element {
+        match part {
+            Part::Text => self.text,
+            Part::Buffer => self.buffer,
+            Part::Gutter => self.gutter,
+            Part::StatusBar => self.status_bar,
+            Part::Message => self.message,
+            Part::Match => self.matched,
+        }
+    }
+
+    // sets one of the THEME_KEYS from brr.conf. returns
+    // false if the value doesn't make sense for the key.
+    pub fn set(&mut self, key: &str, value: &str) -> bool {
+        if key == "buffer-style" {
+            let (reverse, underline) = match value {
+                "reverse" => (true, false),
+                "underline" => (false, true),
+                "color" | "colour" => (false, false),
+                _ => return false,
+            };
+            self.buffer.reverse = reverse;
+            self.buffer.underline = underline;
+            return true
+        }
+
+        let Some(color) = parse_color(value) else {
+            return false
+        };
+        let element = match key.split_once('-') {
+            Some(("text", _)) => &mut self.text,
+            Some(("buffer", _)) => &mut self.buffer,
+            Some(("gutter", _)) => &mut self.gutter,
+            Some(("status", _)) => &mut self.status_bar,
+            Some(("message", _)) => &mut self.message,
+            Some(("match", _)) => &mut self.matched,
+            _ => return false,
+        };
+        if key.ends_with("-fg") {
+            element.fg = Some(color);
+        } else {
+            element.bg = Some(color);
+        }
+        true
+    }
+
+    // see https://no-color.org. the colours are dropped, and
+    // anything that relied on them to stand out goes back to
+    // reverse video like the default theme.
+    pub fn without_color(mut self) -> Self {
+        self.text = Element {
+            fg: None,
+            bg: None,
+            ..self.text
+        };
+        self.message = Element {
+            fg: None,
+            bg: None,
+            ..self.message
+        };
+        for element in [&mut self.buffer, &mut self.gutter, &mut self.status_bar, &mut self.matched] {
+            element.fg = None;
+            element.bg = None;
+            if !element.underline {
+                element.reverse = true;
+            }
+        }
+
+        self
+    }
+}
+
+// NO_COLOR only counts if it's set to something
+pub fn no_color() -> bool {
+    var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+}
+
+// colours can be given as #rrggbb, a number from 0-255 for the
+// 256 colour palette, the name of one of the 16 basic colours,
+// or "default" for the terminal's own colour.
+pub fn parse_color(value: &str) -> Option<Color> {
+    if let Some(hex) = value.strip_prefix('#') {
+        let channel = |range| hex.get(range).and_then(|channel| u8::from_str_radix(channel, 16).ok());
+
+        if hex.len() != 6 {
+            return None
+        }
+        return Some(Color::Rgb {
+            r: channel(0..2)?,
+            g: channel(2..4)?,
+            b: channel(4..6)?,
+        })
+    }
+    if let Ok(ansi) = value.parse::<u8>() {
+        return Some(Color::AnsiValue(ansi))
+    }
+
+    let color = match value {
+        "default" => Color::Reset,
+        "black" => Color::Black,
+        "dark-grey" | "dark-gray" => Color::DarkGrey,
+        "red" => Color::Red,
+        "dark-red" => Color::DarkRed,
+        "green" => Color::Green,
+        "dark-green" => Color::DarkGreen,
+        "yellow" => Color::Yellow,
+        "dark-yellow" => Color::DarkYellow,
+        "blue" => Color::Blue,
+        "dark-blue" => Color::DarkBlue,
+        "magenta" => Color::Magenta,
+        "dark-magenta" => Color::DarkMagenta,
+        "cyan" => Color::Cyan,
+        "dark-cyan" => Color::DarkCyan,
+        "white" => Color::White,
+        "grey" | "gray" => Color::Grey,
+        _ => return None,
+    };
+
+    Some(color)
+}
+
+#[allow(clippy::unnecessary_wraps)] // it's always some, but it reads better in the presets
+fn rgb(r: u8, g: u8, b: u8) -> Option<Color> {
+    Some(Color::Rgb {
+        r,
+        g,
+        b,
+    })
+}
+
+fn colors(fg: Option<Color>, bg: Option<Color>) -> Element {
+    Element {
+        fg,
+        bg,
+        ..Default::default()
+    }
+}
+
+fn underlined(fg: Option<Color>) -> Element {
+    Element {
+        fg,
+        underline: true,
+        ..Default::default()
+    }
+}
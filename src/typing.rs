@@ -0,0 +1,122 @@
+use std::time::{Duration, Instant};
+
+// -----------------
+
+// any gap between keystrokes longer than this ends a burst
+const BURST_GAP: Duration = Duration::from_secs(2);
+
+// keeps track of the rhythm of the session's typing. a burst
+// is a run of keystrokes without any gap longer than BURST_GAP.
+#[derive(Default)]
+pub struct TypingStats {
+    pub keystrokes: usize,
+    pub backspaces: usize,
+    pub longest_pause: Duration,
+    pub bursts: usize,
+    pub longest_burst: usize,
+    first_key: Option<Instant>,
+    last_key: Option<Instant>,
+    current_burst: usize,
+}
+
+impl TypingStats {
+    pub fn record(&mut self, backspace: bool) {
+        let now = Instant::now();
+
+        if let Some(last_key) = self.last_key {
+            let pause = now.duration_since(last_key);
+
+            self.longest_pause = self.longest_pause.max(pause);
+            if pause > BURST_GAP {
+                self.end_burst();
+            }
+        } else {
+            self.first_key = Some(now);
+        }
+
+        if backspace {
+            self.backspaces = self.backspaces.saturating_add(1);
+        }
+        self.keystrokes = self.keystrokes.saturating_add(1);
+        self.current_burst = self.current_burst.saturating_add(1);
+        self.last_key = Some(now);
+    }
+
+    fn end_burst(&mut self) {
+        if self.current_burst > 0 {
+            self.bursts = self.bursts.saturating_add(1);
+            self.longest_burst = self.longest_burst.max(self.current_burst);
+            self.current_burst = 0;
+        }
+    }
+
+    // the burst in progress hasn't been ended by a pause yet,
+    // so count it as finished when we want the numbers
+    pub fn finish(&mut self) {
+        self.end_burst();
+    }
+
+    // time from the first keystroke to the last one
+    pub fn typing_time(&self) -> Duration {
+        match (self.first_key, self.last_key) {
+            (Some(first), Some(last)) => last.duration_since(first),
+            _ => Duration::ZERO,
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)] // see sprint.rs
+    pub fn words_per_minute(&self, words: usize) -> f64 {
+        let minutes = self.typing_time().as_secs_f64() / 60.0;
+
+        if minutes > 0.0 {
+            words as f64 / minutes
+        } else {
+            0.0
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    pub fn backspace_ratio(&self) -> f64 {
+        if self.keystrokes > 0 {
+            self.backspaces as f64 / self.keystrokes as f64
+        } else {
+            0.0
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    pub fn average_burst(&self) -> f64 {
+        if self.bursts > 0 {
+            (self.keystrokes as f64) / (self.bursts as f64)
+        } else {
+            0.0
+        }
+    }
+
+    pub fn summary(&self, words: usize) -> String {
+        if self.keystrokes == 0 {
+            return String::new()
+        }
+
+        format!(
+            "typing this session:\r\n  {:.1} wpm\r\n  longest pause: {}\r\n  {} bursts (avg. {:.0} keys, longest {} keys)\r\n  {:.1}% backspaces\r\n",
+            self.words_per_minute(words),
+            format_duration(self.longest_pause),
+            self.bursts,
+            self.average_burst(),
+            self.longest_burst,
+            self.backspace_ratio() * 100.0,
+        )
+    }
+}
+
+// e.g. "1m 12s"
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+
+    if secs >= 60 {
+        format!("{}m {}s", secs / 60, secs % 60)
+    } else {
+        format!("{secs}s")
+    }
+}